@@ -0,0 +1,52 @@
+use ark_bn254::Fr;
+use ark_std::{test_rng, UniformRand};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use univariate_poly::libs::fiat_shamir::Transcript;
+use univariate_poly::libs::multilinear_poly::MultilinearPoly;
+use univariate_poly::libs::sum_check_protocol::{PartialEvaluation, SumcheckProver};
+use univariate_poly::libs::virtual_poly::VirtualPoly;
+
+// add_i * (w_b + w_c) + mul_i * w_b * w_c, the shape of a GKR layer
+fn layer_shaped_poly(n_vars: usize) -> VirtualPoly<Fr> {
+    let mut rng = test_rng();
+    let mut poly = VirtualPoly::new(n_vars);
+
+    let mut random_table =
+        || MultilinearPoly::new((0..1 << n_vars).map(|_| Fr::rand(&mut rng)).collect());
+
+    let add_i = poly.add_table(random_table());
+    let mul_i = poly.add_table(random_table());
+    let w_b = poly.add_table(random_table());
+    let w_c = poly.add_table(random_table());
+
+    poly.add_term(Fr::from(1), vec![add_i, w_b]);
+    poly.add_term(Fr::from(1), vec![add_i, w_c]);
+    poly.add_term(Fr::from(1), vec![mul_i, w_b, w_c]);
+
+    poly
+}
+
+fn sumcheck_prover(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sumcheck_prover");
+
+    for n_vars in [8, 10, 12] {
+        let poly = layer_shaped_poly(n_vars);
+        let claimed_sum = poly.sum_over_hypercube();
+
+        group.bench_with_input(BenchmarkId::new("linear_time", n_vars), &poly, |b, poly| {
+            b.iter(|| SumcheckProver::prove(poly, claimed_sum, &mut Transcript::new()))
+        });
+
+        let naive = PartialEvaluation(poly.clone());
+        group.bench_with_input(
+            BenchmarkId::new("partial_evaluation", n_vars),
+            &naive,
+            |b, poly| b.iter(|| SumcheckProver::prove(poly, claimed_sum, &mut Transcript::new())),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, sumcheck_prover);
+criterion_main!(benches);
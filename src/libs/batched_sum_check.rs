@@ -0,0 +1,306 @@
+use super::fiat_shamir::{fq_vec_to_bytes, Transcript};
+use super::polynomial::barycentric_evaluate;
+use super::sum_check_protocol::{
+    EvaluationClaim, SumcheckError, SumcheckPoly, SumcheckProver, SumcheckVerifier,
+};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+// one sumcheck for several claims. final_evaluations[i] is instance i at its own point,
+// which the verifier checks against the last round and hands back as a claim
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchedSumcheckProof<F: PrimeField> {
+    pub claimed_sums: Vec<F>,
+    pub round_polys: Vec<Vec<F>>,
+    pub final_evaluations: Vec<F>,
+}
+
+// sum of coefficients[i] * polys[i]. an instance with fewer variables than the batch
+// ignores the leading ones, so it adds 2^(extra variables) * its sum and its own point is
+// the tail of the challenges
+#[derive(Clone)]
+struct BatchedPoly<F: PrimeField, P: SumcheckPoly<F>> {
+    polys: Vec<P>,
+    sums: Vec<F>,
+    coefficients: Vec<F>,
+    n_vars: usize,
+}
+
+impl<F: PrimeField, P: SumcheckPoly<F>> BatchedPoly<F, P> {
+    fn extra_vars(&self, i: usize) -> usize {
+        self.n_vars - self.polys[i].n_vars()
+    }
+}
+
+impl<F: PrimeField, P: SumcheckPoly<F>> SumcheckPoly<F> for BatchedPoly<F, P> {
+    fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    fn get_degree(&self) -> usize {
+        self.polys
+            .iter()
+            .map(|poly| poly.get_degree())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        let mut result = self.clone();
+        result.fold(value);
+        result
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        (0..self.polys.len())
+            .map(|i| {
+                self.coefficients[i] * F::from(2u64).pow([self.extra_vars(i) as u64]) * self.sums[i]
+            })
+            .sum()
+    }
+
+    fn round_polynomial(&self) -> Vec<F> {
+        let degree = SumcheckPoly::get_degree(self);
+        let mut result = vec![F::zero(); degree + 1];
+
+        for (i, poly) in self.polys.iter().enumerate() {
+            let extra_vars = self.extra_vars(i);
+
+            // variable 0 is not one of this instance's, so its round poly is constant
+            let evals = if extra_vars > 0 {
+                vec![F::from(2u64).pow([extra_vars as u64 - 1]) * self.sums[i]; degree + 1]
+            } else {
+                let evals = poly.round_polynomial();
+                (0..=degree as u64)
+                    .map(|x| barycentric_evaluate(&evals, F::from(x)))
+                    .collect()
+            };
+
+            for (total, eval) in result.iter_mut().zip(evals) {
+                *total += self.coefficients[i] * eval;
+            }
+        }
+
+        result
+    }
+
+    fn fold(&mut self, challenge: &F) {
+        for i in 0..self.polys.len() {
+            if self.extra_vars(i) == 0 {
+                self.polys[i].fold(challenge);
+            }
+        }
+        self.n_vars -= 1;
+    }
+}
+
+// sums[i] and the transcript order both follow the order of polys
+fn combination_challenge<F: PrimeField>(
+    claimed_sums: &[F],
+    transcript: &mut Transcript<F>,
+) -> Vec<F> {
+    transcript.append(&fq_vec_to_bytes(claimed_sums));
+    let alpha = transcript.get_random_challenge();
+
+    std::iter::successors(Some(F::one()), |power| Some(*power * alpha))
+        .take(claimed_sums.len())
+        .collect()
+}
+
+fn combined_sum<F: PrimeField>(claimed_sums: &[F], coefficients: &[F], n_vars: &[usize]) -> F {
+    let max_vars = n_vars.iter().copied().max().unwrap_or(0);
+
+    claimed_sums
+        .iter()
+        .zip(coefficients.iter())
+        .zip(n_vars.iter())
+        .map(|((sum, coefficient), vars)| {
+            *coefficient * F::from(2u64).pow([(max_vars - vars) as u64]) * sum
+        })
+        .sum()
+}
+
+pub fn prove_batched<F: PrimeField, P: SumcheckPoly<F>>(
+    polys: &[P],
+    claimed_sums: &[F],
+    transcript: &mut Transcript<F>,
+) -> (BatchedSumcheckProof<F>, Vec<EvaluationClaim<F>>) {
+    if polys.is_empty() || polys.len() != claimed_sums.len() {
+        panic!("need one claimed sum for each of at least one polynomial");
+    }
+
+    let coefficients = combination_challenge(claimed_sums, transcript);
+    let n_vars: Vec<usize> = polys.iter().map(|poly| poly.n_vars()).collect();
+
+    let batched_poly = BatchedPoly {
+        polys: polys.to_vec(),
+        sums: claimed_sums.to_vec(),
+        coefficients: coefficients.clone(),
+        n_vars: *n_vars.iter().max().unwrap(),
+    };
+
+    let (proof, challenges, folded) = SumcheckProver::prove_rounds(
+        &batched_poly,
+        combined_sum(claimed_sums, &coefficients, &n_vars),
+        transcript,
+    );
+
+    let final_evaluations: Vec<F> = folded
+        .polys
+        .iter()
+        .map(|poly| poly.sum_over_hypercube())
+        .collect();
+
+    transcript.append(&fq_vec_to_bytes(&final_evaluations));
+
+    let claims = final_evaluations
+        .iter()
+        .zip(n_vars.iter())
+        .map(|(value, vars)| EvaluationClaim {
+            point: challenges[challenges.len() - vars..].to_vec(),
+            value: *value,
+        })
+        .collect();
+
+    let batched_proof = BatchedSumcheckProof {
+        claimed_sums: claimed_sums.to_vec(),
+        round_polys: proof.round_polys,
+        final_evaluations,
+    };
+
+    (batched_proof, claims)
+}
+
+// n_vars[i] is the verifier's own count for instance i and degree is the largest degree of
+// any instance. every returned claim still has to be checked against its instance
+pub fn verify_batched<F: PrimeField>(
+    proof: &BatchedSumcheckProof<F>,
+    n_vars: &[usize],
+    degree: usize,
+    transcript: &mut Transcript<F>,
+) -> Result<Vec<EvaluationClaim<F>>, SumcheckError> {
+    for got in [proof.claimed_sums.len(), proof.final_evaluations.len()] {
+        if got != n_vars.len() {
+            return Err(SumcheckError::WrongNumberOfInstances {
+                expected: n_vars.len(),
+                got,
+            });
+        }
+    }
+
+    let coefficients = combination_challenge(&proof.claimed_sums, transcript);
+
+    let claim = SumcheckVerifier::verify_rounds(
+        combined_sum(&proof.claimed_sums, &coefficients, n_vars),
+        &proof.round_polys,
+        n_vars.iter().copied().max().unwrap_or(0),
+        degree,
+        false,
+        transcript,
+    )?;
+
+    let combined_evaluation: F = coefficients
+        .iter()
+        .zip(proof.final_evaluations.iter())
+        .map(|(coefficient, value)| *coefficient * value)
+        .sum();
+
+    if combined_evaluation != claim.value {
+        return Err(SumcheckError::FinalEvaluationMismatch);
+    }
+
+    transcript.append(&fq_vec_to_bytes(&proof.final_evaluations));
+
+    Ok(proof
+        .final_evaluations
+        .iter()
+        .zip(n_vars.iter())
+        .map(|(value, vars)| EvaluationClaim {
+            point: claim.point[claim.point.len() - vars..].to_vec(),
+            value: *value,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prove_batched, verify_batched};
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::multilinear_poly::{ProductPoly, SumPoly};
+    use crate::libs::sum_check_protocol::{SumcheckError, SumcheckPoly};
+    use ark_bn254::Fr;
+
+    fn instances() -> Vec<SumPoly<Fr>> {
+        vec![
+            // 3 variables, degree 2
+            SumPoly::new(vec![
+                ProductPoly::new(vec![
+                    to_field(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+                    to_field(vec![0, 5, 1, 2, 9, 9, 4, 1]),
+                ]),
+                ProductPoly::new(vec![to_field(vec![3, 0, 0, 1, 2, 0, 0, 7])]),
+            ]),
+            // 2 variables, degree 3
+            SumPoly::new(vec![ProductPoly::new(vec![
+                to_field(vec![1, 2, 3, 4]),
+                to_field(vec![2, 2, 0, 1]),
+                to_field(vec![5, 1, 1, 3]),
+            ])]),
+            // 1 variable, degree 1
+            SumPoly::new(vec![ProductPoly::new(vec![to_field(vec![6, 9])])]),
+        ]
+    }
+
+    #[test]
+    fn it_batches_instances_with_different_sizes() {
+        let polys = instances();
+        let claimed_sums: Vec<Fr> = polys.iter().map(|poly| poly.sum_over_hypercube()).collect();
+
+        let (proof, prover_claims) = prove_batched(&polys, &claimed_sums, &mut Transcript::new());
+
+        assert_eq!(proof.round_polys.len(), 3);
+        assert!(proof
+            .round_polys
+            .iter()
+            .all(|round_poly| round_poly.len() == 4));
+
+        let claims = verify_batched(&proof, &[3, 2, 1], 3, &mut Transcript::new()).unwrap();
+
+        assert_eq!(claims, prover_claims);
+        for (poly, claim) in polys.iter().zip(claims.iter()) {
+            assert_eq!(claim.point.len(), poly.n_vars());
+            assert_eq!(claim.value, poly.evaluate(claim.point.clone()));
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_claim_or_evaluation() {
+        let polys = instances();
+        let mut claimed_sums: Vec<Fr> =
+            polys.iter().map(|poly| poly.sum_over_hypercube()).collect();
+        claimed_sums[1] += Fr::from(1);
+
+        // round 0 is a padding round for instance 1, so the bad sum only shows in round 1
+        let (proof, _) = prove_batched(&polys, &claimed_sums, &mut Transcript::new());
+        assert_eq!(
+            verify_batched(&proof, &[3, 2, 1], 3, &mut Transcript::new()),
+            Err(SumcheckError::RoundSumMismatch { round: 1 })
+        );
+
+        claimed_sums[1] -= Fr::from(1);
+        let (mut proof, _) = prove_batched(&polys, &claimed_sums, &mut Transcript::new());
+        proof.final_evaluations[2] += Fr::from(1);
+        assert_eq!(
+            verify_batched(&proof, &[3, 2, 1], 3, &mut Transcript::new()),
+            Err(SumcheckError::FinalEvaluationMismatch)
+        );
+        assert_eq!(
+            verify_batched(&proof, &[3, 2], 3, &mut Transcript::new()),
+            Err(SumcheckError::WrongNumberOfInstances {
+                expected: 2,
+                got: 3
+            })
+        );
+    }
+}
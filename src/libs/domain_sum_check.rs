@@ -0,0 +1,340 @@
+use super::fiat_shamir::Transcript;
+use super::polynomial::{barycentric_evaluate, lagrange_basis};
+use super::sum_check_protocol::{
+    EvaluationClaim, SumcheckError, SumcheckPoly, SumcheckProof, SumcheckVerifier,
+    SumcheckVerifierState,
+};
+use super::virtual_poly::VirtualPoly;
+use ark_ff::PrimeField;
+
+// a VirtualPoly over {0, ..., k_0 - 1} x {0, ..., k_1 - 1} x ... instead of the hypercube.
+// tables hold the values on that grid with variable 0 as the most significant digit and are
+// extended with degree k_i - 1 in variable i. a boolean table with its first s variables
+// read as one variable of arity 2^s is the univariate skip: one round of degree
+// (2^s - 1) * d in place of s rounds
+#[derive(Clone, Debug, PartialEq)]
+pub struct DomainPoly<F: PrimeField> {
+    pub(crate) tables: Vec<Vec<F>>,
+    pub(crate) terms: Vec<(F, Vec<usize>)>,
+    pub(crate) arities: Vec<usize>,
+}
+
+impl<F: PrimeField> DomainPoly<F> {
+    pub fn new(arities: Vec<usize>) -> Self {
+        if arities.iter().any(|arity| *arity < 2) {
+            panic!("every variable needs at least two values");
+        }
+
+        Self {
+            tables: vec![],
+            terms: vec![],
+            arities,
+        }
+    }
+
+    // collapses the first skip variables of every table into one variable of arity 2^skip
+    pub fn with_univariate_skip(poly: &VirtualPoly<F>, skip: usize) -> Self {
+        if skip == 0 || skip > poly.n_vars {
+            panic!("can only skip between 1 and n_vars variables");
+        }
+
+        let mut arities = vec![1 << skip];
+        arities.extend(vec![2; poly.n_vars - skip]);
+
+        Self {
+            tables: poly
+                .tables
+                .iter()
+                .map(|table| table.evals.clone())
+                .collect(),
+            terms: poly.terms.clone(),
+            arities,
+        }
+    }
+
+    pub fn arities(&self) -> &[usize] {
+        &self.arities
+    }
+
+    pub fn add_table(&mut self, table: Vec<F>) -> usize {
+        if table.len() != self.arities.iter().product::<usize>() {
+            panic!("table does not cover the domain");
+        }
+
+        self.tables.push(table);
+
+        self.tables.len() - 1
+    }
+
+    pub fn add_term(&mut self, coefficient: F, table_indices: Vec<usize>) {
+        if table_indices
+            .iter()
+            .any(|index| *index >= self.tables.len())
+        {
+            panic!("term refers to a table that was never added");
+        }
+
+        self.terms.push((coefficient, table_indices));
+    }
+
+    // largest number of tables multiplied in one term
+    pub fn term_degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|(_, indices)| indices.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn evaluate(&self, point: &[F]) -> F {
+        if point.len() != self.arities.len() {
+            panic!("point does not match the number of variables");
+        }
+
+        let mut poly = self.clone();
+        for value in point {
+            poly.fold_first(value);
+        }
+
+        poly.sum_over_domain()
+    }
+
+    pub fn sum_over_domain(&self) -> F {
+        let size = self.tables.first().map_or(0, |table| table.len());
+
+        (0..size)
+            .map(|i| {
+                self.terms
+                    .iter()
+                    .map(|(coefficient, indices)| {
+                        *coefficient
+                            * indices
+                                .iter()
+                                .map(|index| self.tables[*index][i])
+                                .product::<F>()
+                    })
+                    .sum::<F>()
+            })
+            .sum()
+    }
+
+    // table(value, rest) = sum_x basis_x(value) * table(x, rest)
+    fn fold_first(&mut self, value: &F) {
+        let arity = self.arities.remove(0);
+        let basis = lagrange_basis(arity, *value);
+
+        for table in self.tables.iter_mut() {
+            let rest = table.len() / arity;
+
+            *table = (0..rest)
+                .map(|j| {
+                    basis
+                        .iter()
+                        .enumerate()
+                        .map(|(x, l)| *l * table[x * rest + j])
+                        .sum()
+                })
+                .collect();
+        }
+    }
+}
+
+impl<F: PrimeField> SumcheckPoly<F> for DomainPoly<F> {
+    fn n_vars(&self) -> usize {
+        self.arities.len()
+    }
+
+    // of the round polynomial in variable 0
+    fn get_degree(&self) -> usize {
+        self.term_degree() * (self.arities[0] - 1)
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        let mut result = self.clone();
+        result.fold_first(value);
+        result
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        self.sum_over_domain()
+    }
+
+    // like the boolean round_evaluations: every table is a degree arity - 1 polynomial in
+    // variable 0 through its values on the domain, extended to 0..=degree and multiplied out
+    fn round_polynomial(&self) -> Vec<F> {
+        let arity = self.arities[0];
+        let degree = SumcheckPoly::get_degree(self);
+        let rest = self.tables[0].len() / arity;
+
+        let mut result = vec![F::zero(); degree + 1];
+        let mut table_points = vec![vec![F::zero(); degree + 1]; self.tables.len()];
+
+        for j in 0..rest {
+            for (table, points) in self.tables.iter().zip(table_points.iter_mut()) {
+                let values: Vec<F> = (0..arity).map(|x| table[x * rest + j]).collect();
+
+                for (x, point) in points.iter_mut().enumerate() {
+                    *point = if x < arity {
+                        values[x]
+                    } else {
+                        barycentric_evaluate(&values, F::from(x as u64))
+                    };
+                }
+            }
+
+            for (coefficient, indices) in self.terms.iter() {
+                for (x, eval) in result.iter_mut().enumerate() {
+                    *eval += *coefficient
+                        * indices
+                            .iter()
+                            .map(|index| table_points[*index][x])
+                            .product::<F>();
+                }
+            }
+        }
+
+        result
+    }
+
+    fn fold(&mut self, challenge: &F) {
+        self.fold_first(challenge);
+    }
+}
+
+// SumcheckProver::prove works on a DomainPoly as is. term_degree is the verifier's bound on
+// the number of tables per term, round i then has term_degree * (arities[i] - 1) + 1 values
+// which have to sum to the claim over 0..arities[i]
+pub fn verify<F: PrimeField>(
+    proof: &SumcheckProof<F>,
+    arities: &[usize],
+    term_degree: usize,
+    transcript: &mut Transcript<F>,
+) -> Result<EvaluationClaim<F>, SumcheckError> {
+    SumcheckVerifier::run_rounds(
+        SumcheckVerifierState::over_domain(proof.claimed_sum, arities, term_degree),
+        &proof.round_polys,
+        false,
+        transcript,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify, DomainPoly};
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::gkr_circuits_impl::{Gate, Layer, Operation};
+    use crate::libs::gkr_protocol::get_fbc_poly;
+    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use crate::libs::sum_check_protocol::{SumcheckError, SumcheckProver, SumcheckVerifier};
+    use crate::libs::virtual_poly::VirtualPoly;
+    use ark_bn254::Fr;
+
+    // 3 * a * b - c over 3 variables
+    fn boolean_poly() -> VirtualPoly<Fr> {
+        let mut poly = VirtualPoly::new(3);
+        let a = poly.add_table(MultilinearPoly::new(to_field(vec![1, 2, 3, 4, 5, 6, 7, 8])));
+        let b = poly.add_table(MultilinearPoly::new(to_field(vec![0, 5, 1, 2, 9, 9, 4, 1])));
+        let c = poly.add_table(MultilinearPoly::new(to_field(vec![3, 0, 0, 1, 2, 0, 0, 7])));
+        poly.add_term(Fr::from(3), vec![a, b]);
+        poly.add_term(-Fr::from(1), vec![c]);
+        poly
+    }
+
+    #[test]
+    fn skipping_one_variable_is_plain_sumcheck() {
+        let poly = boolean_poly();
+        let claimed_sum = poly.sum_over_hypercube();
+        let domain_poly = DomainPoly::with_univariate_skip(&poly, 1);
+
+        let (plain, plain_claim) =
+            SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+        let (skip, skip_claim) =
+            SumcheckProver::prove(&domain_poly, claimed_sum, &mut Transcript::new());
+
+        assert_eq!(plain, skip);
+        assert_eq!(plain_claim, skip_claim);
+        assert_eq!(
+            verify(&skip, &[2, 2, 2], 2, &mut Transcript::new()),
+            SumcheckVerifier::verify(&plain, 3, 2, &mut Transcript::new())
+        );
+    }
+
+    #[test]
+    fn univariate_skip_collapses_the_first_rounds() {
+        let poly = boolean_poly();
+        let claimed_sum = poly.sum_over_hypercube();
+        let domain_poly = DomainPoly::with_univariate_skip(&poly, 2);
+
+        assert_eq!(domain_poly.arities(), &[4, 2]);
+        assert_eq!(domain_poly.sum_over_domain(), claimed_sum);
+
+        let (proof, prover_claim) =
+            SumcheckProver::prove(&domain_poly, claimed_sum, &mut Transcript::new());
+
+        assert_eq!(proof.round_polys.len(), 2);
+        assert_eq!(proof.round_polys[0].len(), 7);
+
+        let claim = verify(&proof, &[4, 2], 2, &mut Transcript::new()).unwrap();
+
+        assert_eq!(claim, prover_claim);
+        assert_eq!(claim.value, domain_poly.evaluate(&claim.point));
+
+        // on boolean points the skip extension agrees with the multilinear one
+        assert_eq!(
+            domain_poly.evaluate(&to_field(vec![2, 1])),
+            poly.evaluate(&to_field(vec![1, 0, 1]))
+        );
+    }
+
+    #[test]
+    fn it_runs_over_a_ternary_domain() {
+        let mut poly = DomainPoly::new(vec![3, 3]);
+        let a = poly.add_table(to_field(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]));
+        let b = poly.add_table(to_field(vec![2, 0, 1, 1, 0, 3, 5, 2, 2]));
+        poly.add_term(Fr::from(1), vec![a, b]);
+
+        let claimed_sum = poly.sum_over_domain();
+        assert_eq!(claimed_sum, Fr::from(96));
+
+        let (proof, _) = SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+        let claim = verify(&proof, &[3, 3], 2, &mut Transcript::new()).unwrap();
+
+        assert_eq!(claim.value, poly.evaluate(&claim.point));
+
+        let (wrong, _) =
+            SumcheckProver::prove(&poly, claimed_sum + Fr::from(1), &mut Transcript::new());
+        assert_eq!(
+            verify(&wrong, &[3, 3], 2, &mut Transcript::new()),
+            Err(SumcheckError::RoundSumMismatch { round: 0 })
+        );
+        assert_eq!(
+            verify(&proof, &[3, 3], 1, &mut Transcript::new()),
+            Err(SumcheckError::WrongRoundPolyLength {
+                round: 0,
+                expected: 3,
+                got: 5
+            })
+        );
+    }
+
+    #[test]
+    fn it_skips_rounds_of_a_gkr_layer() {
+        let layer = Layer::new(vec![
+            Gate::new(Fr::from(1), Fr::from(2), Operation::Mul),
+            Gate::new(Fr::from(3), Fr::from(4), Operation::Add),
+        ]);
+        let w = to_field(vec![1, 2, 3, 4]);
+
+        let fbc_poly = VirtualPoly::from(&get_fbc_poly(&[Fr::from(5)], layer, &w, &w));
+        let claimed_sum = fbc_poly.sum_over_hypercube();
+        let domain_poly = DomainPoly::with_univariate_skip(&fbc_poly, 2);
+
+        let (proof, _) = SumcheckProver::prove(&domain_poly, claimed_sum, &mut Transcript::new());
+        let claim = verify(&proof, &[4, 2, 2], 2, &mut Transcript::new()).unwrap();
+
+        assert_eq!(fbc_poly.n_vars, 4);
+        assert_eq!(claim.point.len(), 3);
+        assert_eq!(claim.value, domain_poly.evaluate(&claim.point));
+    }
+}
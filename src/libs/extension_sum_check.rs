@@ -0,0 +1,145 @@
+use super::fiat_shamir::Transcript;
+use super::sum_check_protocol::{EvaluationClaim, SumcheckProof, SumcheckProver};
+use super::virtual_poly::VirtualPoly;
+use ark_ff::{Field, PrimeField};
+
+// sumcheck for tables over a small prime field B with challenges from an extension E, see
+// SumcheckProver::prove_over_extension. the tables stay in B until the first challenge
+// folds them into E
+pub fn prove<B: PrimeField, E: Field<BasePrimeField = B>>(
+    poly: &VirtualPoly<B>,
+    claimed_sum: B,
+    transcript: &mut Transcript<B>,
+) -> (SumcheckProof<E>, EvaluationClaim<E>) {
+    SumcheckProver::prove_over_extension(poly, claimed_sum, transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prove;
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::goldilocks::{Goldilocks, GoldilocksExt2};
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use crate::libs::sum_check_protocol::{SumcheckError, SumcheckProver, SumcheckVerifier};
+    use crate::libs::virtual_poly::VirtualPoly;
+    use ark_ff::Field;
+
+    fn to_goldilocks(values: Vec<u64>) -> Vec<Goldilocks> {
+        values.into_iter().map(Goldilocks::from).collect()
+    }
+
+    // 3 * a * b * c - b over 3 variables, with 64 bit sized witness values
+    fn witness_poly() -> VirtualPoly<Goldilocks> {
+        let mut poly = VirtualPoly::new(3);
+        let a = poly.add_table(MultilinearPoly::new(to_goldilocks(vec![
+            u64::MAX,
+            2,
+            3,
+            1 << 40,
+            5,
+            6,
+            7,
+            8,
+        ])));
+        let b = poly.add_table(MultilinearPoly::new(to_goldilocks(vec![
+            0,
+            5,
+            1 << 63,
+            2,
+            9,
+            9,
+            4,
+            1,
+        ])));
+        let c = poly.add_table(MultilinearPoly::new(to_goldilocks(vec![
+            3, 0, 0, 1, 2, 0, 0, 7,
+        ])));
+        poly.add_term(Goldilocks::from(3), vec![a, b, c]);
+        poly.add_term(-Goldilocks::from(1), vec![b]);
+        poly
+    }
+
+    fn evaluate_in(poly: &VirtualPoly<Goldilocks>, point: &[GoldilocksExt2]) -> GoldilocksExt2 {
+        let table_evals: Vec<GoldilocksExt2> = poly
+            .tables
+            .iter()
+            .map(|table| table.evaluate_in(point))
+            .collect();
+
+        poly.terms
+            .iter()
+            .map(|(coefficient, indices)| {
+                GoldilocksExt2::from_base_prime_field(*coefficient)
+                    * indices
+                        .iter()
+                        .map(|index| table_evals[*index])
+                        .product::<GoldilocksExt2>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn it_proves_with_extension_challenges() {
+        let poly = witness_poly();
+        let claimed_sum = poly.sum_over_hypercube();
+
+        let (proof, prover_claim) =
+            prove::<Goldilocks, GoldilocksExt2>(&poly, claimed_sum, &mut Transcript::new());
+
+        let claim = SumcheckVerifier::verify(&proof, 3, 3, &mut Transcript::new()).unwrap();
+
+        assert_eq!(claim, prover_claim);
+        assert_eq!(claim.value, evaluate_in(&poly, &claim.point));
+
+        // round 0 is still in the base field
+        assert!(proof.round_polys[0]
+            .iter()
+            .all(|eval| eval.c1 == Goldilocks::from(0)));
+        assert!(claim.point.iter().all(|r| r.c1 != Goldilocks::from(0)));
+    }
+
+    #[test]
+    fn base_field_challenges_match_the_prime_field_prover() {
+        let poly = witness_poly();
+        let claimed_sum = poly.sum_over_hypercube();
+
+        let expected = SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+
+        assert_eq!(
+            prove::<Goldilocks, Goldilocks>(&poly, claimed_sum, &mut Transcript::new()),
+            expected
+        );
+    }
+
+    #[test]
+    fn a_single_table_stays_a_multilinear_poly() {
+        let table = witness_poly().tables[0].clone();
+        let claimed_sum = table.evals.iter().sum();
+
+        let (proof, claim) = SumcheckProver::prove_over_extension::<_, GoldilocksExt2, _>(
+            &table,
+            claimed_sum,
+            &mut Transcript::new(),
+        );
+
+        assert_eq!(
+            SumcheckVerifier::verify(&proof, 3, 1, &mut Transcript::new()),
+            Ok(claim.clone())
+        );
+        assert_eq!(claim.value, table.evaluate_in(&claim.point));
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_sum() {
+        let poly = witness_poly();
+        let claimed_sum = poly.sum_over_hypercube() + Goldilocks::from(1);
+
+        let (proof, _) =
+            prove::<Goldilocks, GoldilocksExt2>(&poly, claimed_sum, &mut Transcript::new());
+
+        assert_eq!(
+            SumcheckVerifier::verify(&proof, 3, 3, &mut Transcript::new()),
+            Err(SumcheckError::RoundSumMismatch { round: 0 })
+        );
+    }
+}
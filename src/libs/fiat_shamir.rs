@@ -1,4 +1,4 @@
-use ark_ff::{BigInteger, PrimeField};
+use ark_ff::{BigInteger, Field, PrimeField};
 use sha3::{Digest, Keccak256};
 use std::marker::PhantomData;
 
@@ -8,6 +8,12 @@ pub struct Transcript<F: PrimeField> {
     hasher: Keccak256,
 }
 
+impl<F: PrimeField> Default for Transcript<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<F: PrimeField> Transcript<F> {
     pub fn new() -> Self {
         Self {
@@ -27,6 +33,15 @@ impl<F: PrimeField> Transcript<F> {
 
         F::from_le_bytes_mod_order(&random_challenge)
     }
+
+    // one challenge per coordinate over F. for E = F this is get_random_challenge
+    pub fn get_extension_challenge<E: Field<BasePrimeField = F>>(&mut self) -> E {
+        let coordinates: Vec<F> = (0..E::extension_degree())
+            .map(|_| self.get_random_challenge())
+            .collect();
+
+        E::from_base_prime_field_elems(coordinates).unwrap()
+    }
 }
 
 pub fn fq_vec_to_bytes<F: PrimeField>(values: &[F]) -> Vec<u8> {
@@ -36,6 +51,16 @@ pub fn fq_vec_to_bytes<F: PrimeField>(values: &[F]) -> Vec<u8> {
         .collect()
 }
 
+// the coordinates over the prime field, so prime field values give fq_vec_to_bytes
+pub fn field_vec_to_bytes<E: Field>(values: &[E]) -> Vec<u8> {
+    let coordinates: Vec<E::BasePrimeField> = values
+        .iter()
+        .flat_map(|x| x.to_base_prime_field_elements())
+        .collect();
+
+    fq_vec_to_bytes(&coordinates)
+}
+
 #[cfg(test)]
 mod test {
     use super::Transcript;
@@ -1,8 +1,9 @@
 use ark_ff::PrimeField;
 // use multilinear_polynomial::multilinear_polynomial_evaluation::MultilinearPoly;
 use multilinear_poly::MultilinearPoly;
+use sparse_multilinear_poly::SparseMultilinearPoly;
 
-use super::multilinear_poly;
+use super::{multilinear_poly, sparse_multilinear_poly};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Operation {
@@ -50,44 +51,64 @@ impl<F: PrimeField> Layer<F> {
         Self { gates }
     }
 
-    // adds the outputs of each gate in the layer and returns an array of the outputs
-    fn get_layer_poly(&self) -> Vec<F> {
+    // the outputs recorded in the gates, by Gate::new or the last Circuit::evaluate
+    pub fn get_layer_poly(&self) -> Vec<F> {
         self.gates.iter().map(|gate| gate.output).collect()
     }
 
     pub fn get_add_mul_i(&self, op: Operation) -> MultilinearPoly<F> {
-        let n_bits = self.get_bits_for_gates();
-        let layer_size = 1 << n_bits;
-        let mut poly_eval = vec![F::zero(); layer_size];
+        self.get_sparse_add_mul_i(op).to_dense()
+    }
+
+    // one F::one() per gate, so the wiring predicate is kept sparse
+    pub fn get_sparse_add_mul_i(&self, op: Operation) -> SparseMultilinearPoly<F> {
+        let n_bits = self.get_bits_for_gates() as usize;
 
         let gate_values = self.gate_to_bits();
-        for (gate_value, gate) in gate_values.into_iter().zip(&self.gates) {
-            if gate.op == op {
-                poly_eval[gate_value] = F::one();
-            }
-        }
+        let entries = gate_values
+            .into_iter()
+            .zip(&self.gates)
+            .filter(|(_, gate)| gate.op == op)
+            .map(|(gate_value, _)| (gate_value, F::one()))
+            .collect();
 
-        MultilinearPoly::new(poly_eval)
+        SparseMultilinearPoly::new(n_bits, entries)
     }
 
     fn get_bits_for_gates(&self) -> u32 {
+        (self.output_bits() + 2 * self.input_bits()) as u32
+    }
+
+    pub fn num_gates(&self) -> usize {
+        self.gates.len()
+    }
+
+    // bits of a, the gate index, rounded up for layers that are not a power of two wide.
+    // a single gate still takes one bit
+    pub fn output_bits(&self) -> usize {
         let n_gates = self.gates.len();
         assert!(n_gates > 0, "There must be at least one gate in the layer.");
 
-        if n_gates == 1 {
-            3
-        } else {
-            let n_gates_log = n_gates.ilog2();
-            let n_bits = n_gates_log + 1;
-            n_gates_log + (n_bits * 2)
-        }
+        (n_gates.next_power_of_two().ilog2() as usize).max(1)
+    }
+
+    // bits of b and c, the indices into the layer below, which holds two values per gate.
+    // the layer sumcheck runs over b || c
+    pub fn input_bits(&self) -> usize {
+        let n_gates = self.gates.len();
+        assert!(n_gates > 0, "There must be at least one gate in the layer.");
+
+        (2 * n_gates).next_power_of_two().ilog2() as usize
     }
 
+    // gate i reads inputs 2i and 2i + 1 of the layer below: a || b || c
     fn gate_to_bits(&self) -> Vec<usize> {
+        let input_bits = self.input_bits();
+
         self.gates
             .iter()
             .enumerate()
-            .map(|(i, _)| 5 * i + 1)
+            .map(|(i, _)| (i << (2 * input_bits)) | ((2 * i) << input_bits) | (2 * i + 1))
             .collect()
     }
 }
@@ -109,24 +130,86 @@ impl<F: PrimeField> Circuit<F> {
                 Layer::new(gates)
             })
             .collect();
-        Self { layers }
+        let circuit = Self { layers };
+
+        if let Some(i) = circuit.misshapen_layer() {
+            panic!(
+                "layer {} has {} gates, it must be half as wide as the {} gates below it",
+                i,
+                circuit.layers[i].num_gates(),
+                circuit.layers[i - 1].num_gates()
+            );
+        }
+
+        circuit
+    }
+
+    // the first layer, counted from the inputs, that is not half as wide as the layer below.
+    // every gate reads two values of the layer below, so any other width drops or misses
+    // some of them
+    pub fn misshapen_layer(&self) -> Option<usize> {
+        (1..self.layers.len())
+            .find(|&i| 2 * self.layers[i].num_gates() != self.layers[i - 1].num_gates())
+    }
+
+    // every layer from the inputs up as its width and then one byte per gate, 0 for Add and 1
+    // for Mul. what a transcript absorbs to commit to the circuit
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.layers
+            .iter()
+            .flat_map(|layer| {
+                let ops = layer.gates.iter().map(|gate| match gate.op {
+                    Operation::Add => 0,
+                    Operation::Mul => 1,
+                });
+
+                (layer.num_gates() as u64)
+                    .to_le_bytes()
+                    .into_iter()
+                    .chain(ops)
+            })
+            .collect()
     }
 
+    // evaluations, recorded in the gates as well
     pub fn evaluate(&mut self, inputs: &[F]) -> Vec<Vec<F>> {
-        let mut result = Vec::new();
-        let mut current_inputs = inputs.to_vec();
-
-        for layer in &mut self.layers {
-            for (gate, input_pair) in layer.gates.iter_mut().zip(current_inputs.chunks_exact(2)) {
-                let (l_input, r_input) = (input_pair[0], input_pair[1]);
-                gate.l_input = l_input;
-                gate.r_input = r_input;
-                gate.output = gate.op.apply(l_input, r_input);
+        let result = self.evaluations(inputs);
+
+        for (i, (layer, outputs)) in self.layers.iter_mut().zip(result.iter()).enumerate() {
+            let layer_inputs = if i == 0 { inputs } else { &result[i - 1] };
+
+            for ((gate, input_pair), output) in layer
+                .gates
+                .iter_mut()
+                .zip(layer_inputs.chunks_exact(2))
+                .zip(outputs.iter())
+            {
+                gate.l_input = input_pair[0];
+                gate.r_input = input_pair[1];
+                gate.output = *output;
             }
-            let layer_outputs = layer.get_layer_poly();
-            result.push(layer_outputs.clone());
-            current_inputs = layer_outputs;
         }
+
+        result
+    }
+
+    // the same layer outputs without recording them in the gates, so one circuit can be
+    // shared across many evaluations
+    pub fn evaluations(&self, inputs: &[F]) -> Vec<Vec<F>> {
+        let mut result: Vec<Vec<F>> = Vec::with_capacity(self.layers.len());
+
+        for layer in &self.layers {
+            let current_inputs = result.last().map_or(inputs, |outputs| outputs.as_slice());
+            let layer_outputs = layer
+                .gates
+                .iter()
+                .zip(current_inputs.chunks_exact(2))
+                .map(|(gate, input_pair)| gate.op.apply(input_pair[0], input_pair[1]))
+                .collect();
+
+            result.push(layer_outputs);
+        }
+
         result
     }
 }
@@ -168,9 +251,45 @@ mod test {
 
         let mut circuit = Circuit::new(structure);
 
+        assert_eq!(circuit.evaluations(&inputs), expected_evaluations);
+
         let evaluations = circuit.evaluate(&inputs);
 
         assert_eq!(evaluations, expected_evaluations);
+
+        // evaluate also leaves every gate holding its output
+        for (layer, expected) in circuit.layers.iter().zip(expected_evaluations.iter()) {
+            assert_eq!(&layer.get_layer_poly(), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "layer 1 has 1 gates")]
+    fn it_rejects_a_layer_that_is_not_half_as_wide() {
+        Circuit::<Fq>::new(vec![
+            vec![
+                Operation::Mul,
+                Operation::Mul,
+                Operation::Add,
+                Operation::Add,
+            ],
+            vec![Operation::Add],
+        ]);
+    }
+
+    #[test]
+    fn it_encodes_every_layer_width_and_op() {
+        let circuit = Circuit::<Fq>::new(vec![
+            vec![Operation::Mul, Operation::Add],
+            vec![Operation::Add],
+        ]);
+
+        let mut expected = 2u64.to_le_bytes().to_vec();
+        expected.extend([1, 0]);
+        expected.extend(1u64.to_le_bytes());
+        expected.push(0);
+
+        assert_eq!(circuit.to_bytes(), expected);
     }
 
     #[test]
@@ -242,6 +361,44 @@ mod test {
         assert_eq!(expected_mul_1_poly, mul_1_poly.evals);
         assert_eq!(expected_mul_2_poly, mul_2_poly.evals);
     }
+
+    #[test]
+    fn it_wires_each_gate_to_its_input_pair() {
+        let gate_1 = Gate::new(Fq::from(1), Fq::from(2), Operation::Add);
+        let gate_2 = Gate::new(Fq::from(3), Fq::from(4), Operation::Mul);
+
+        let layer = Layer::new(vec![gate_1, gate_2]);
+
+        // a = 0, b = 00, c = 01 and a = 1, b = 10, c = 11
+        let add_i = layer.get_sparse_add_mul_i(Operation::Add);
+        let mul_i = layer.get_sparse_add_mul_i(Operation::Mul);
+
+        assert_eq!(add_i.evals, vec![(0b0_00_01, Fq::from(1))]);
+        assert_eq!(mul_i.evals, vec![(0b1_10_11, Fq::from(1))]);
+        assert_eq!(add_i.to_dense(), layer.get_add_mul_i(Operation::Add));
+    }
+
+    #[test]
+    fn it_wires_layers_that_are_not_a_power_of_two_wide() {
+        let gates = [Operation::Add, Operation::Mul, Operation::Add]
+            .into_iter()
+            .map(|op| Gate::new(Fq::from(0), Fq::from(0), op))
+            .collect();
+
+        let layer = Layer::new(gates);
+
+        // 3 gates take 2 bits of a, their 6 inputs 3 bits each of b and c
+        let add_i = layer.get_sparse_add_mul_i(Operation::Add);
+        let mul_i = layer.get_sparse_add_mul_i(Operation::Mul);
+
+        assert_eq!(add_i.n_vars, 8);
+        assert_eq!(
+            add_i.evals,
+            vec![(0b00_000_001, Fq::from(1)), (0b10_100_101, Fq::from(1))]
+        );
+        assert_eq!(mul_i.evals, vec![(0b01_010_011, Fq::from(1))]);
+        assert_eq!(mul_i.to_dense(), layer.get_add_mul_i(Operation::Mul));
+    }
 }
 
 // enum GateOperations {
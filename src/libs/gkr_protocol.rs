@@ -1,86 +1,812 @@
 use super::gkr_circuits_impl::{Circuit, Layer, Operation};
 
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, UniformRand, Zero};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+use super::domain_sum_check::DomainPoly;
 use super::fiat_shamir::{fq_vec_to_bytes, Transcript};
 use super::multilinear_poly::{
-    MultilinearPoly, {ProductPoly, SumPoly},
+    MultilinearPoly, PaddedMultilinearPoly, Padding, {ProductPoly, SumPoly},
 };
-use super::sum_check_protocol::{gkr_prove, gkr_verify};
-
-#[derive(Debug)]
+use super::pedersen::{
+    points_to_bytes, public_commitment, DotProductProof, EqualityProof, Opening, PedersenParams,
+    ProductProof,
+};
+use super::polynomial::lagrange_basis;
+use super::sum_check_protocol::{
+    compress_round_poly, CompressedSumcheckProof, SumcheckError, SumcheckPoly, SumcheckProverState,
+    SumcheckVerifierState,
+};
+use super::virtual_poly::VirtualPoly;
+use super::zk_sum_check::{self, commitment_len, ZkSumcheckProof};
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_std::rand::Rng;
+use std::fmt;
+
+// the outputs are not part of the proof, the verifier brings the ones it expects
+#[derive(Clone, Debug)]
 pub struct Proof<F: PrimeField> {
-    output_poly: MultilinearPoly<F>,
-    proof_polynomials: Vec<Vec<Vec<F>>>,
-    claimed_evaluations: Vec<(F, F)>,
+    pub(crate) sumcheck_proofs: Vec<CompressedSumcheckProof<F>>,
+    pub(crate) claimed_evaluations: Vec<(F, F)>,
 }
 
-pub fn prove<F: PrimeField>(circuit: &mut Circuit<F>, inputs: &[F]) -> Proof<F> {
-    let mut transcript = Transcript::<F>::new();
-    let mut circuit_evaluations = circuit.evaluate(inputs);
-    let mut w_0 = circuit_evaluations.last().unwrap().to_vec();
+impl<F: PrimeField> Proof<F> {
+    // one per layer, from the output down
+    pub fn sumcheck_proofs(&self) -> &[CompressedSumcheckProof<F>] {
+        &self.sumcheck_proofs
+    }
+
+    // W_{i + 1}(r_b) and W_{i + 1}(r_c) for every layer but the one reading the inputs
+    pub fn claimed_evaluations(&self) -> &[(F, F)] {
+        &self.claimed_evaluations
+    }
+}
+
+// what the prover sends, in protocol order: the outputs, then for every layer its sumcheck
+// rounds and, unless the layer reads the inputs, W_{i + 1} at r_b and r_c. the outputs are
+// answered with one challenge per output bit, the evaluations with alpha and beta and every
+// round with one challenge
+#[derive(Clone, Debug, PartialEq)]
+pub enum GkrMessage<F: PrimeField> {
+    // one per output gate, unpadded
+    Outputs(Vec<F>),
+    // compressed, p(1) comes from the running claim
+    RoundPoly(Vec<F>),
+    Evaluations(F, F),
+}
 
-    if w_0.len() == 1 {
-        w_0.push(F::zero());
+impl<F: PrimeField> GkrMessage<F> {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            GkrMessage::Outputs(values) | GkrMessage::RoundPoly(values) => fq_vec_to_bytes(values),
+            GkrMessage::Evaluations(o_1, o_2) => fq_vec_to_bytes(&[*o_1, *o_2]),
+        }
     }
-    let output_poly = MultilinearPoly::new(w_0);
+}
 
-    let (mut claimed_sum, random_challenge) = initiate_protocol(&mut transcript, &output_poly);
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProveError {
+    EmptyCircuit,
+    MisshapenLayer { layer: usize },
+    WrongNumberOfInputs { expected: usize, got: usize },
+}
 
-    let num_layers = circuit.layers.len();
-    let mut proof_polys = Vec::with_capacity(num_layers);
-    let mut claimed_evaluations = Vec::with_capacity(num_layers.saturating_sub(1));
-    let mut current_rb = Vec::new();
-    let mut current_rc = Vec::new();
-    let mut alpha = F::zero();
-    let mut beta = F::zero();
-
-    circuit_evaluations.reverse();
-    let mut layers = circuit.layers.clone();
-    layers.reverse();
-
-    for (idx, layer) in layers.into_iter().enumerate() {
-        let w_i = if idx == num_layers - 1 {
-            inputs.to_vec()
+impl fmt::Display for ProveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProveError::EmptyCircuit => write!(f, "the circuit has no layers"),
+            ProveError::MisshapenLayer { layer } => {
+                write!(f, "layer {} is not half as wide as the layer below", layer)
+            }
+            ProveError::WrongNumberOfInputs { expected, got } => {
+                write!(f, "expected {} inputs, got {}", expected, got)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProveError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerifyError {
+    WrongNumberOfOutputs { expected: usize, got: usize },
+    WrongNumberOfInputs { expected: usize, got: usize },
+    WrongNumberOfLayers { expected: usize, got: usize },
+    EmptyCircuit,
+    MisshapenLayer { layer: usize },
+    UnexpectedMessage,
+    Sumcheck { layer: usize, error: SumcheckError },
+    ClaimMismatch { layer: usize },
+    LayerMismatch { layer: usize },
+    Incomplete,
+    InputMismatch,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::WrongNumberOfOutputs { expected, got } => {
+                write!(f, "expected {} outputs, got {}", expected, got)
+            }
+            VerifyError::WrongNumberOfInputs { expected, got } => {
+                write!(f, "expected {} inputs, got {}", expected, got)
+            }
+            VerifyError::WrongNumberOfLayers { expected, got } => {
+                write!(f, "expected {} layers, got {}", expected, got)
+            }
+            VerifyError::EmptyCircuit => write!(f, "the circuit has no layers"),
+            VerifyError::MisshapenLayer { layer } => {
+                write!(f, "layer {} is not half as wide as the layer below", layer)
+            }
+            VerifyError::UnexpectedMessage => write!(f, "message out of protocol order"),
+            VerifyError::Sumcheck { layer, error } => write!(f, "layer {}: {}", layer, error),
+            VerifyError::ClaimMismatch { layer } => {
+                write!(f, "layer {} sumcheck does not start from the claim", layer)
+            }
+            VerifyError::LayerMismatch { layer } => {
+                write!(f, "layer {} evaluations do not match its sumcheck", layer)
+            }
+            VerifyError::Incomplete => write!(f, "the protocol ended early"),
+            VerifyError::InputMismatch => {
+                write!(f, "the last layer does not open the input commitment")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+// both sides walk the same schedule, the phase says what comes next
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Phase {
+    Outputs,
+    OutputChallenge,
+    Round,
+    RoundChallenge,
+    Evaluations,
+    Alpha,
+    Beta,
+    Done,
+}
+
+impl Phase {
+    fn wants_challenge(self) -> bool {
+        matches!(
+            self,
+            Phase::OutputChallenge | Phase::RoundChallenge | Phase::Alpha | Phase::Beta
+        )
+    }
+}
+
+// layers counted from the output, the order the protocol walks them in
+fn layer_from_output<F: PrimeField>(circuit: &Circuit<F>, layer: usize) -> &Layer<F> {
+    &circuit.layers[circuit.layers.len() - 1 - layer]
+}
+
+// Circuit::misshapen_layer, counted from the output like every other layer in here
+fn misshapen_layer<F: PrimeField>(circuit: &Circuit<F>) -> Option<usize> {
+    circuit
+        .misshapen_layer()
+        .map(|layer| circuit.layers.len() - 1 - layer)
+}
+
+// a layer as a poly over exactly the bits the wiring predicates use, zero padded when it
+// is not a power of two wide
+fn padded_poly<F: PrimeField>(values: &[F], n_vars: usize) -> MultilinearPoly<F> {
+    PaddedMultilinearPoly::with_n_vars(values.to_vec(), Padding::Zeros, n_vars).into_poly()
+}
+
+// the outputs, and what every layer reads counted from the output, padded to its input bits
+fn evaluate_layers<F: PrimeField>(circuit: &Circuit<F>, inputs: &[F]) -> (Vec<F>, Vec<Vec<F>>) {
+    let mut circuit_evaluations = circuit.evaluations(inputs);
+    let outputs = circuit_evaluations.pop().unwrap();
+
+    let mut layer_inputs = vec![inputs.to_vec()];
+    layer_inputs.extend(circuit_evaluations);
+    layer_inputs.reverse();
+
+    let layer_inputs = layer_inputs
+        .iter()
+        .enumerate()
+        .map(|(i, w)| padded_poly(w, layer_from_output(circuit, i).input_bits()).evals)
+        .collect();
+
+    (outputs, layer_inputs)
+}
+
+// the univariate skip of a layer, at most all of b. 1 is plain sumcheck
+fn layer_skip<F: PrimeField>(layer: &Layer<F>, skip: usize) -> usize {
+    skip.clamp(1, layer.input_bits())
+}
+
+// the layer sumcheck runs over b || c, with the first skip bits of b as one variable over
+// {0, ..., 2^skip - 1}
+fn layer_arities<F: PrimeField>(layer: &Layer<F>, skip: usize) -> Vec<usize> {
+    let skip = layer_skip(layer, skip);
+
+    let mut arities = vec![1 << skip];
+    arities.extend(vec![2; 2 * layer.input_bits() - skip]);
+
+    arities
+}
+
+// <values, point_weights(point, skip)> extends values to point: the multilinear extension,
+// or with skip > 1 the first skip bits through the Lagrange basis of their domain, which
+// is what a DomainPoly folds to
+fn point_weights<F: PrimeField>(point: &[F], skip: usize) -> Vec<F> {
+    if skip <= 1 {
+        return MultilinearPoly::eq(point).evals;
+    }
+
+    let rest = MultilinearPoly::eq(&point[1..]).evals;
+
+    lagrange_basis(1 << skip, point[0])
+        .iter()
+        .flat_map(|l| rest.iter().map(move |e| *l * e))
+        .collect()
+}
+
+// r_b and r_c of a finished layer sumcheck as weights over the layer below
+fn split_point_weights<F: PrimeField>(
+    layer: &Layer<F>,
+    point: &[F],
+    skip: usize,
+) -> (Vec<F>, Vec<F>) {
+    let (r_b, r_c) = point.split_at(point.len() - layer.input_bits());
+
+    (
+        point_weights(r_b, layer_skip(layer, skip)),
+        point_weights(r_c, 1),
+    )
+}
+
+// alpha * W(r_b) + beta * W(r_c) as weights, i.e. what the next layer's gate bits a sum with
+fn combine_weights<F: PrimeField>(weights: &(Vec<F>, Vec<F>), alpha: F, beta: F) -> Vec<F> {
+    weights
+        .0
+        .iter()
+        .zip(weights.1.iter())
+        .map(|(b, c)| alpha * b + beta * c)
+        .collect()
+}
+
+fn inner_product<F: PrimeField>(values: &[F], weights: &[F]) -> F {
+    values.iter().zip(weights.iter()).map(|(v, w)| *v * w).sum()
+}
+
+// a layer's sumcheck poly, over the hypercube or with the univariate skip
+#[derive(Clone)]
+enum LayerPoly<F: PrimeField> {
+    Boolean(SumPoly<F>),
+    Skip(DomainPoly<F>),
+}
+
+impl<F: PrimeField> LayerPoly<F> {
+    fn new(layer: &Layer<F>, w: &[F], a_weights: &[F], skip: usize) -> Self {
+        let poly = get_weighted_fbc_poly(layer, w, a_weights);
+
+        match layer_skip(layer, skip) {
+            1 => LayerPoly::Boolean(poly),
+            skip => LayerPoly::Skip(DomainPoly::with_univariate_skip(
+                &VirtualPoly::from(&poly),
+                skip,
+            )),
+        }
+    }
+}
+
+impl<F: PrimeField> SumcheckPoly<F> for LayerPoly<F> {
+    fn n_vars(&self) -> usize {
+        match self {
+            LayerPoly::Boolean(poly) => poly.n_vars(),
+            LayerPoly::Skip(poly) => poly.n_vars(),
+        }
+    }
+
+    fn get_degree(&self) -> usize {
+        match self {
+            LayerPoly::Boolean(poly) => SumcheckPoly::get_degree(poly),
+            LayerPoly::Skip(poly) => SumcheckPoly::get_degree(poly),
+        }
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        match self {
+            LayerPoly::Boolean(poly) => {
+                LayerPoly::Boolean(SumcheckPoly::partial_evaluate(poly, value))
+            }
+            LayerPoly::Skip(poly) => LayerPoly::Skip(SumcheckPoly::partial_evaluate(poly, value)),
+        }
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        match self {
+            LayerPoly::Boolean(poly) => SumcheckPoly::sum_over_hypercube(poly),
+            LayerPoly::Skip(poly) => SumcheckPoly::sum_over_hypercube(poly),
+        }
+    }
+
+    fn round_polynomial(&self) -> Vec<F> {
+        match self {
+            LayerPoly::Boolean(poly) => poly.round_polynomial(),
+            LayerPoly::Skip(poly) => poly.round_polynomial(),
+        }
+    }
+
+    fn fold(&mut self, challenge: &F) {
+        match self {
+            LayerPoly::Boolean(poly) => poly.fold(challenge),
+            LayerPoly::Skip(poly) => poly.fold(challenge),
+        }
+    }
+}
+
+// the prover as a state machine: next_message while it is not waiting for a challenge,
+// receive_challenge while it is. layers are walked from the output down
+pub struct GkrProver<'a, F: PrimeField> {
+    circuit: &'a Circuit<F>,
+    // layer_inputs[i] is the layer below layer i counted from the output, padded to its
+    // input bits. the last one is the circuit input
+    layer_inputs: Vec<Vec<F>>,
+    outputs: Vec<F>,
+    output_point: Vec<F>,
+    phase: Phase,
+    layer: usize,
+    claim: F,
+    skip: usize,
+    sumcheck: Option<SumcheckProverState<F, LayerPoly<F>>>,
+    // r_b and r_c of the last finished sumcheck, as weights
+    weights: (Vec<F>, Vec<F>),
+    evaluations: (F, F),
+    alpha: F,
+}
+
+impl<'a, F: PrimeField> GkrProver<'a, F> {
+    pub fn new(circuit: &'a Circuit<F>, inputs: &[F]) -> Self {
+        Self::with_univariate_skip(circuit, inputs, 1)
+    }
+
+    // every layer sumcheck starts with one round over the first skip bits of b, capped at
+    // the layer's input bits. the verifier has to use the same skip
+    pub fn with_univariate_skip(circuit: &'a Circuit<F>, inputs: &[F], skip: usize) -> Self {
+        let (outputs, layer_inputs) = evaluate_layers(circuit, inputs);
+
+        Self {
+            circuit,
+            layer_inputs,
+            outputs,
+            output_point: vec![],
+            phase: Phase::Outputs,
+            layer: 0,
+            claim: F::zero(),
+            skip,
+            sumcheck: None,
+            weights: (vec![], vec![]),
+            evaluations: (F::zero(), F::zero()),
+            alpha: F::zero(),
+        }
+    }
+
+    pub fn wants_challenge(&self) -> bool {
+        self.phase.wants_challenge()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.phase == Phase::Done
+    }
+
+    // the circuit outputs, before padding
+    pub fn outputs(&self) -> &[F] {
+        &self.outputs
+    }
+
+    // the layer being proven, 0 is the output layer
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    // what the current layer's sumcheck starts from
+    pub fn claim(&self) -> F {
+        self.claim
+    }
+
+    pub fn next_message(&mut self) -> GkrMessage<F> {
+        match self.phase {
+            Phase::Outputs => {
+                self.phase = Phase::OutputChallenge;
+
+                GkrMessage::Outputs(self.outputs.clone())
+            }
+            Phase::Round => {
+                self.phase = Phase::RoundChallenge;
+
+                let round_poly = self.sumcheck.as_ref().unwrap().round_poly();
+                GkrMessage::RoundPoly(compress_round_poly(&round_poly))
+            }
+            Phase::Evaluations => {
+                let w = &self.layer_inputs[self.layer];
+
+                self.evaluations = (
+                    inner_product(w, &self.weights.0),
+                    inner_product(w, &self.weights.1),
+                );
+                self.phase = Phase::Alpha;
+
+                GkrMessage::Evaluations(self.evaluations.0, self.evaluations.1)
+            }
+            Phase::Done => panic!("the protocol is over"),
+            _ => panic!("the prover is waiting for a challenge"),
+        }
+    }
+
+    pub fn receive_challenge(&mut self, challenge: F) {
+        match self.phase {
+            Phase::OutputChallenge => {
+                self.output_point.push(challenge);
+
+                let output_layer = layer_from_output(self.circuit, 0);
+                if self.output_point.len() < output_layer.output_bits() {
+                    return;
+                }
+
+                self.claim = padded_poly(&self.outputs, output_layer.output_bits())
+                    .evaluate(self.output_point.clone());
+
+                let a_weights = point_weights(&self.output_point, 1);
+                let poly =
+                    LayerPoly::new(output_layer, &self.layer_inputs[0], &a_weights, self.skip);
+                self.start_sumcheck(poly);
+            }
+            Phase::RoundChallenge => {
+                let sumcheck = self.sumcheck.as_mut().unwrap();
+                sumcheck.receive_challenge(challenge);
+
+                if sumcheck.is_done() {
+                    self.end_sumcheck();
+                } else {
+                    self.phase = Phase::Round;
+                }
+            }
+            Phase::Alpha => {
+                self.alpha = challenge;
+                self.phase = Phase::Beta;
+            }
+            Phase::Beta => {
+                let (alpha, beta) = (self.alpha, challenge);
+                let (o_1, o_2) = self.evaluations;
+                self.claim = alpha * o_1 + beta * o_2;
+                self.layer += 1;
+
+                let a_weights = combine_weights(&self.weights, alpha, beta);
+                let poly = LayerPoly::new(
+                    layer_from_output(self.circuit, self.layer),
+                    &self.layer_inputs[self.layer],
+                    &a_weights,
+                    self.skip,
+                );
+
+                self.start_sumcheck(poly);
+            }
+            _ => panic!("the prover is not waiting for a challenge"),
+        }
+    }
+
+    fn start_sumcheck(&mut self, poly: LayerPoly<F>) {
+        let sumcheck = SumcheckProverState::new(&poly);
+        let is_done = sumcheck.is_done();
+
+        self.sumcheck = Some(sumcheck);
+        self.phase = Phase::Round;
+
+        if is_done {
+            self.end_sumcheck();
+        }
+    }
+
+    fn end_sumcheck(&mut self) {
+        let (point, _) = self.sumcheck.take().unwrap().finish();
+        let layer = layer_from_output(self.circuit, self.layer);
+        self.weights = split_point_weights(layer, &point, self.skip);
+
+        self.phase = if self.layer == self.circuit.layers.len() - 1 {
+            Phase::Done
         } else {
-            circuit_evaluations[idx + 1].clone()
+            Phase::Evaluations
         };
+    }
+}
+
+// the verifier as a state machine: receive while it is not waiting for a challenge,
+// challenge_with or sample_challenge while it is, finish once the prover is done
+pub struct GkrVerifier<'a, F: PrimeField> {
+    circuit: &'a Circuit<F>,
+    inputs: &'a [F],
+    phase: Phase,
+    layer: usize,
+    claim: F,
+    outputs: Vec<F>,
+    output_point: Vec<F>,
+    skip: usize,
+    sumcheck: Option<SumcheckVerifierState<F>>,
+    // what the gate bits a of this layer sum with, and r_b, r_c of its finished sumcheck
+    a_weights: Vec<F>,
+    weights: (Vec<F>, Vec<F>),
+    sumcheck_value: F,
+    evaluations: (F, F),
+    alpha: F,
+}
+
+impl<'a, F: PrimeField> GkrVerifier<'a, F> {
+    pub fn new(circuit: &'a Circuit<F>, inputs: &'a [F]) -> Self {
+        Self::with_univariate_skip(circuit, inputs, 1)
+    }
+
+    pub fn with_univariate_skip(circuit: &'a Circuit<F>, inputs: &'a [F], skip: usize) -> Self {
+        Self {
+            circuit,
+            inputs,
+            phase: Phase::Outputs,
+            layer: 0,
+            claim: F::zero(),
+            outputs: vec![],
+            output_point: vec![],
+            skip,
+            sumcheck: None,
+            a_weights: vec![],
+            weights: (vec![], vec![]),
+            sumcheck_value: F::zero(),
+            evaluations: (F::zero(), F::zero()),
+            alpha: F::zero(),
+        }
+    }
+
+    pub fn wants_challenge(&self) -> bool {
+        self.phase.wants_challenge()
+    }
+
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    // what the current layer's sumcheck starts from
+    pub fn claim(&self) -> F {
+        self.claim
+    }
+
+    // the outputs the prover claimed, which is what a successful finish proves
+    pub fn outputs(&self) -> &[F] {
+        &self.outputs
+    }
+
+    pub fn receive(&mut self, message: GkrMessage<F>) -> Result<(), VerifyError> {
+        match (self.phase, message) {
+            (Phase::Outputs, GkrMessage::Outputs(outputs)) => {
+                if self.circuit.layers.is_empty() {
+                    return Err(VerifyError::EmptyCircuit);
+                }
+
+                if let Some(layer) = misshapen_layer(self.circuit) {
+                    return Err(VerifyError::MisshapenLayer { layer });
+                }
+
+                let expected = layer_from_output(self.circuit, 0).num_gates();
+                if outputs.len() != expected {
+                    return Err(VerifyError::WrongNumberOfOutputs {
+                        expected,
+                        got: outputs.len(),
+                    });
+                }
+
+                self.outputs = outputs;
+                self.phase = Phase::OutputChallenge;
+            }
+            (Phase::Round, GkrMessage::RoundPoly(round_poly)) => {
+                self.sumcheck
+                    .as_mut()
+                    .unwrap()
+                    .receive_compressed_round_poly(round_poly)
+                    .map_err(|error| VerifyError::Sumcheck {
+                        layer: self.layer,
+                        error,
+                    })?;
+
+                self.phase = Phase::RoundChallenge;
+            }
+            (Phase::Evaluations, GkrMessage::Evaluations(o_1, o_2)) => {
+                self.check_layer(o_1, o_2)?;
+
+                self.evaluations = (o_1, o_2);
+                self.phase = Phase::Alpha;
+            }
+            _ => return Err(VerifyError::UnexpectedMessage),
+        }
+
+        Ok(())
+    }
+
+    pub fn challenge_with(&mut self, challenge: F) {
+        match self.phase {
+            Phase::OutputChallenge => {
+                self.output_point.push(challenge);
+
+                let output_bits = layer_from_output(self.circuit, 0).output_bits();
+                if self.output_point.len() < output_bits {
+                    return;
+                }
+
+                self.claim =
+                    padded_poly(&self.outputs, output_bits).evaluate(self.output_point.clone());
+                self.a_weights = point_weights(&self.output_point, 1);
+
+                self.start_sumcheck();
+            }
+            Phase::RoundChallenge => {
+                let sumcheck = self.sumcheck.as_mut().unwrap();
+                sumcheck.challenge_with(challenge);
+
+                if sumcheck.is_done() {
+                    self.end_sumcheck();
+                } else {
+                    self.phase = Phase::Round;
+                }
+            }
+            Phase::Alpha => {
+                self.alpha = challenge;
+                self.phase = Phase::Beta;
+            }
+            Phase::Beta => {
+                let (alpha, beta) = (self.alpha, challenge);
+                let (o_1, o_2) = self.evaluations;
+                self.claim = alpha * o_1 + beta * o_2;
+                self.a_weights = combine_weights(&self.weights, alpha, beta);
+                self.layer += 1;
+
+                self.start_sumcheck();
+            }
+            _ => panic!("the verifier is not waiting for a challenge"),
+        }
+    }
+
+    pub fn sample_challenge<R: Rng>(&mut self, rng: &mut R) -> F {
+        let challenge = F::rand(rng);
+        self.challenge_with(challenge);
+
+        challenge
+    }
 
-        let fbc_poly = if idx == 0 {
-            get_fbc_poly(random_challenge, layer, &w_i, &w_i)
+    // the last layer reads the inputs, which the verifier evaluates itself
+    pub fn finish(self) -> Result<(), VerifyError> {
+        if self.phase != Phase::Done {
+            return Err(VerifyError::Incomplete);
+        }
+
+        // every gate of the input layer reads two inputs
+        let expected = 2 * layer_from_output(self.circuit, self.layer).num_gates();
+        if self.inputs.len() != expected {
+            return Err(VerifyError::WrongNumberOfInputs {
+                expected,
+                got: self.inputs.len(),
+            });
+        }
+
+        let input_bits = layer_from_output(self.circuit, self.layer).input_bits();
+        let inputs = padded_poly(self.inputs, input_bits).evals;
+        let o_1 = inner_product(&inputs, &self.weights.0);
+        let o_2 = inner_product(&inputs, &self.weights.1);
+
+        self.check_layer(o_1, o_2)
+    }
+
+    fn start_sumcheck(&mut self) {
+        let arities = layer_arities(layer_from_output(self.circuit, self.layer), self.skip);
+
+        // add_i * (w_b + w_c) + mul_i * w_b * w_c over b || c
+        self.sumcheck = Some(SumcheckVerifierState::over_domain(self.claim, &arities, 2));
+        self.phase = Phase::Round;
+    }
+
+    fn end_sumcheck(&mut self) {
+        // every round was received, so the claim is there
+        let claim = self.sumcheck.take().unwrap().finish().unwrap();
+        let layer = layer_from_output(self.circuit, self.layer);
+
+        self.weights = split_point_weights(layer, &claim.point, self.skip);
+        self.sumcheck_value = claim.value;
+
+        self.phase = if self.layer == self.circuit.layers.len() - 1 {
+            Phase::Done
         } else {
-            get_folded_fbc_poly(layer, &w_i, &w_i, &current_rb, &current_rc, alpha, beta)
+            Phase::Evaluations
         };
+    }
+
+    fn check_layer(&self, o_1: F, o_2: F) -> Result<(), VerifyError> {
+        let layer = layer_from_output(self.circuit, self.layer);
+
+        let (a_r, m_r) =
+            get_weighted_wiring(layer, &self.a_weights, &self.weights.0, &self.weights.1);
+        let expected_claim = a_r * (o_1 + o_2) + m_r * o_1 * o_2;
+
+        if expected_claim != self.sumcheck_value {
+            return Err(VerifyError::LayerMismatch { layer: self.layer });
+        }
+
+        Ok(())
+    }
+}
+
+// what a prover needs before evaluating anything: a circuit the protocol can walk and two
+// inputs for every gate of the bottom layer
+fn check_circuit<F: PrimeField>(circuit: &Circuit<F>, inputs: &[F]) -> Result<(), ProveError> {
+    if circuit.layers.is_empty() {
+        return Err(ProveError::EmptyCircuit);
+    }
+
+    if let Some(layer) = misshapen_layer(circuit) {
+        return Err(ProveError::MisshapenLayer { layer });
+    }
 
-        let sum_check_proof = gkr_prove(claimed_sum, &fbc_poly, &mut transcript);
-        proof_polys.push(sum_check_proof.proof_polynomials);
+    let expected = 2 * circuit.layers[0].num_gates();
+    if inputs.len() != expected {
+        return Err(ProveError::WrongNumberOfInputs {
+            expected,
+            got: inputs.len(),
+        });
+    }
 
-        if idx < num_layers - 1 {
-            let next_poly = MultilinearPoly::new(w_i);
-            let mid = sum_check_proof.random_challenges.len() / 2;
-            let (r_b, r_c) = sum_check_proof.random_challenges.split_at(mid);
+    Ok(())
+}
 
-            let o_1 = next_poly.evaluate(r_b.to_vec());
-            let o_2 = next_poly.evaluate(r_c.to_vec());
-            current_rb = r_b.to_vec();
-            current_rc = r_c.to_vec();
+// the statement comes first: the shape of every layer and the inputs. the outputs alone
+// would leave the inputs free to move wherever the last layer's weights do not look
+fn absorb_statement<F: PrimeField>(
+    circuit: &Circuit<F>,
+    inputs: &[F],
+    transcript: &mut Transcript<F>,
+) {
+    transcript.append(&circuit.to_bytes());
+    transcript.append(&fq_vec_to_bytes(inputs));
+}
 
-            transcript.append(&fq_vec_to_bytes(&[o_1]));
-            alpha = transcript.get_random_challenge();
+// the Fiat-Shamir version: every message is absorbed, every challenge drawn from the
+// transcript. returns the circuit outputs along with the proof
+pub fn prove<F: PrimeField>(
+    circuit: &Circuit<F>,
+    inputs: &[F],
+) -> Result<(Vec<F>, Proof<F>), ProveError> {
+    prove_with_univariate_skip(circuit, inputs, 1)
+}
 
-            transcript.append(&fq_vec_to_bytes(&[o_2]));
-            beta = transcript.get_random_challenge();
+// every layer sumcheck opens with one round over the first skip bits of b, see
+// GkrProver::with_univariate_skip. the proof only verifies with the same skip
+pub fn prove_with_univariate_skip<F: PrimeField>(
+    circuit: &Circuit<F>,
+    inputs: &[F],
+    skip: usize,
+) -> Result<(Vec<F>, Proof<F>), ProveError> {
+    check_circuit(circuit, inputs)?;
 
-            claimed_sum = (alpha * o_1) + (beta * o_2);
-            claimed_evaluations.push((o_1, o_2));
+    let mut transcript = Transcript::<F>::new();
+    absorb_statement(circuit, inputs, &mut transcript);
+
+    let mut prover = GkrProver::with_univariate_skip(circuit, inputs, skip);
+
+    let mut sumcheck_proofs: Vec<CompressedSumcheckProof<F>> = Vec::new();
+    let mut claimed_evaluations = Vec::new();
+
+    while !prover.is_done() {
+        if prover.wants_challenge() {
+            prover.receive_challenge(transcript.get_random_challenge());
+            continue;
+        }
+
+        let message = prover.next_message();
+        transcript.append(&message.to_bytes());
+
+        match message {
+            // returned below, never stored in the proof
+            GkrMessage::Outputs(_) => {}
+            GkrMessage::RoundPoly(round_poly) => {
+                if sumcheck_proofs.len() == prover.layer() {
+                    sumcheck_proofs.push(CompressedSumcheckProof {
+                        claimed_sum: prover.claim(),
+                        round_polys: vec![],
+                    });
+                }
+
+                sumcheck_proofs[prover.layer()].round_polys.push(round_poly);
+            }
+            GkrMessage::Evaluations(o_1, o_2) => claimed_evaluations.push((o_1, o_2)),
         }
     }
 
-    Proof {
-        output_poly,
-        proof_polynomials: proof_polys,
+    let proof = Proof {
+        sumcheck_proofs,
         claimed_evaluations,
-    }
+    };
+
+    Ok((prover.outputs().to_vec(), proof))
 }
 
 // pub fn prove<F: PrimeField>(circuit: &mut Circuit<F>, inputs: &[F]) -> Proof<F> {
@@ -151,88 +877,345 @@ pub fn prove<F: PrimeField>(circuit: &mut Circuit<F>, inputs: &[F]) -> Proof<F>
 //     }
 // }
 
-pub fn verify<F: PrimeField>(proof: Proof<F>, mut circuit: Circuit<F>, inputs: &[F]) -> bool {
-    let mut transcript = Transcript::<F>::new();
-
-    let (mut current_claim, init_random_challenge) =
-        initiate_protocol(&mut transcript, &proof.output_poly);
+// the outputs come from the caller, not the proof. the circuit, the inputs and then the
+// outputs are absorbed before any challenge, so Ok means the circuit maps inputs to exactly
+// claimed_outputs
+pub fn verify<F: PrimeField>(
+    proof: &Proof<F>,
+    circuit: &Circuit<F>,
+    inputs: &[F],
+    claimed_outputs: &[F],
+) -> Result<(), VerifyError> {
+    verify_with_univariate_skip(proof, circuit, inputs, claimed_outputs, 1)
+}
 
-    let mut alpha = F::zero();
-    let mut beta = F::zero();
-    let mut prev_sumcheck_random_challenges = Vec::new();
+pub fn verify_with_univariate_skip<F: PrimeField>(
+    proof: &Proof<F>,
+    circuit: &Circuit<F>,
+    inputs: &[F],
+    claimed_outputs: &[F],
+    skip: usize,
+) -> Result<(), VerifyError> {
+    replay(proof, circuit, inputs, claimed_outputs, skip)?.finish()
+}
 
-    circuit.layers.reverse();
+// runs the proof through a verifier over the transcript, up to the check against the inputs
+fn replay<'a, F: PrimeField>(
+    proof: &Proof<F>,
+    circuit: &'a Circuit<F>,
+    inputs: &'a [F],
+    claimed_outputs: &[F],
+    skip: usize,
+) -> Result<GkrVerifier<'a, F>, VerifyError> {
     let num_layers = circuit.layers.len();
+    if num_layers == 0 {
+        return Err(VerifyError::EmptyCircuit);
+    }
 
-    for (i, layer) in circuit.layers.iter().enumerate() {
-        let sum_check_verify = gkr_verify(
-            proof.proof_polynomials[i].clone(),
-            current_claim,
-            &mut transcript,
-        );
+    if let Some(layer) = misshapen_layer(circuit) {
+        return Err(VerifyError::MisshapenLayer { layer });
+    }
 
-        if !sum_check_verify.verified {
-            return false;
+    // the inputs are part of the statement, so they are checked before they are absorbed
+    let expected = 2 * circuit.layers[0].num_gates();
+    if inputs.len() != expected {
+        return Err(VerifyError::WrongNumberOfInputs {
+            expected,
+            got: inputs.len(),
+        });
+    }
+
+    for (expected, got) in [
+        (num_layers, proof.sumcheck_proofs.len()),
+        (num_layers - 1, proof.claimed_evaluations.len()),
+    ] {
+        if expected != got {
+            return Err(VerifyError::WrongNumberOfLayers { expected, got });
         }
+    }
 
-        let current_random_challenge = sum_check_verify.random_challenges;
+    let mut transcript = Transcript::<F>::new();
+    absorb_statement(circuit, inputs, &mut transcript);
 
-        let (o_1, o_2) = if i == num_layers - 1 {
-            evaluate_input_poly(inputs, &current_random_challenge)
-        } else {
-            proof.claimed_evaluations[i]
-        };
+    let mut verifier = GkrVerifier::with_univariate_skip(circuit, inputs, skip);
 
-        let expected_claim = if i == 0 {
-            get_verifier_claim(
-                layer,
-                init_random_challenge,
-                &current_random_challenge,
-                o_1,
-                o_2,
-            )
-        } else {
-            get_folded_verifier_claim(
-                layer,
-                &current_random_challenge,
-                &prev_sumcheck_random_challenges,
-                o_1,
-                o_2,
-                alpha,
-                beta,
-            )
-        };
+    let mut send = |verifier: &mut GkrVerifier<F>, message: GkrMessage<F>| {
+        transcript.append(&message.to_bytes());
+        verifier.receive(message)?;
 
-        if expected_claim != sum_check_verify.final_claimed_sum {
-            return false;
+        while verifier.wants_challenge() {
+            verifier.challenge_with(transcript.get_random_challenge());
         }
 
-        prev_sumcheck_random_challenges = current_random_challenge;
+        Ok(())
+    };
 
-        transcript.append(&fq_vec_to_bytes(&[o_1]));
-        alpha = transcript.get_random_challenge();
+    send(&mut verifier, GkrMessage::Outputs(claimed_outputs.to_vec()))?;
 
-        transcript.append(&fq_vec_to_bytes(&[o_2]));
-        beta = transcript.get_random_challenge();
+    for (layer, sumcheck_proof) in proof.sumcheck_proofs.iter().enumerate() {
+        if sumcheck_proof.claimed_sum != verifier.claim() {
+            return Err(VerifyError::ClaimMismatch { layer });
+        }
+
+        for round_poly in sumcheck_proof.round_polys.iter() {
+            send(&mut verifier, GkrMessage::RoundPoly(round_poly.clone()))?;
+        }
 
-        current_claim = (alpha * o_1) + (beta * o_2);
+        if let Some((o_1, o_2)) = proof.claimed_evaluations.get(layer) {
+            send(&mut verifier, GkrMessage::Evaluations(*o_1, *o_2))?;
+        }
     }
 
-    true
+    Ok(verifier)
 }
 
-fn initiate_protocol<F: PrimeField>(
-    transcript: &mut Transcript<F>,
-    output_poly: &MultilinearPoly<F>,
-) -> (F, F) {
-    transcript.append(&fq_vec_to_bytes(&output_poly.evals));
+// W_{i + 1}(r_b), W_{i + 1}(r_c) and their product, all committed. the sumcheck's committed
+// f(r) is then add_i * (b + c) + mul_i * b * c, which the verifier forms homomorphically
+#[derive(Clone, Debug)]
+pub struct ZkLayerProof<C: SWCurveConfig> {
+    pub(crate) sumcheck_proof: ZkSumcheckProof<C::ScalarField, PedersenParams<C>>,
+    pub(crate) evaluations: [Projective<C>; 3],
+    pub(crate) product_proof: ProductProof<C>,
+    pub(crate) layer_proof: EqualityProof<C>,
+}
 
-    let random_challenge = transcript.get_random_challenge();
-    let m_0 = output_poly.evaluate(vec![random_challenge]);
+// the zero-knowledge version: the verifier gets a commitment to the inputs instead of the
+// inputs, every layer runs the masked sumcheck and nothing but the outputs is in the clear
+#[derive(Clone, Debug)]
+pub struct ZkProof<C: SWCurveConfig> {
+    pub(crate) layer_proofs: Vec<ZkLayerProof<C>>,
+    // the last claim is <inputs, alpha * eq(r_b, x) + beta * eq(r_c, x)>
+    pub(crate) input_proof: DotProductProof<C>,
+}
+
+impl<C: SWCurveConfig> ZkProof<C> {
+    // one per layer, from the output down
+    pub fn layer_proofs(&self) -> &[ZkLayerProof<C>] {
+        &self.layer_proofs
+    }
+}
 
-    transcript.append(&fq_vec_to_bytes(&[m_0]));
+// enough generators for every layer sumcheck and for the inputs
+pub fn zk_params_len<F: PrimeField>(circuit: &Circuit<F>) -> usize {
+    let input_len = circuit
+        .layers
+        .first()
+        .map_or(0, |layer| 1 << layer.input_bits());
 
-    (m_0, random_challenge)
+    circuit
+        .layers
+        .iter()
+        .map(|layer| commitment_len(2 * layer.input_bits(), 2))
+        .fold(input_len, usize::max)
+}
+
+// the inputs padded like the input layer reads them, with the blind prove_zk needs
+pub fn commit_inputs<C: SWCurveConfig, R: Rng>(
+    params: &PedersenParams<C>,
+    circuit: &Circuit<C::ScalarField>,
+    inputs: &[C::ScalarField],
+    rng: &mut R,
+) -> (Projective<C>, C::ScalarField) {
+    let blind = C::ScalarField::rand(rng);
+    let padded = padded_poly(inputs, circuit.layers[0].input_bits()).evals;
+
+    (params.commit(&padded, blind), blind)
+}
+
+fn absorb_outputs<C: SWCurveConfig>(
+    circuit: &Circuit<C::ScalarField>,
+    input_commitment: &Projective<C>,
+    outputs: &[C::ScalarField],
+    transcript: &mut Transcript<C::ScalarField>,
+) -> (Vec<C::ScalarField>, C::ScalarField) {
+    transcript.append(&circuit.to_bytes());
+    transcript.append(&points_to_bytes(&[*input_commitment]));
+    transcript.append(&fq_vec_to_bytes(outputs));
+
+    let output_bits = layer_from_output(circuit, 0).output_bits();
+    let output_point: Vec<C::ScalarField> = (0..output_bits)
+        .map(|_| transcript.get_random_challenge())
+        .collect();
+    let claim = padded_poly(outputs, output_bits).evaluate(output_point.clone());
+
+    (output_point, claim)
+}
+
+// input_blind is the one commit_inputs handed back. returns the outputs along with the proof
+pub fn prove_zk<C: SWCurveConfig, R: Rng>(
+    params: &PedersenParams<C>,
+    circuit: &Circuit<C::ScalarField>,
+    inputs: &[C::ScalarField],
+    input_blind: C::ScalarField,
+    rng: &mut R,
+) -> Result<(Vec<C::ScalarField>, ZkProof<C>), ProveError> {
+    check_circuit(circuit, inputs)?;
+
+    let mut transcript = Transcript::new();
+    let (outputs, layer_inputs) = evaluate_layers(circuit, inputs);
+    let input_commitment = params.commit(layer_inputs.last().unwrap(), input_blind);
+
+    let (output_point, output_claim) =
+        absorb_outputs(circuit, &input_commitment, &outputs, &mut transcript);
+
+    // the output claim is public, every later one is committed
+    let mut claim = Opening {
+        value: output_claim,
+        blind: C::ScalarField::zero(),
+    };
+    let mut a_weights = point_weights(&output_point, 1);
+    let mut layer_proofs = Vec::with_capacity(circuit.layers.len());
+
+    for (layer_index, w) in layer_inputs.iter().enumerate() {
+        let layer = layer_from_output(circuit, layer_index);
+        let poly = get_weighted_fbc_poly(layer, w, &a_weights);
+
+        let (sumcheck_proof, sumcheck_point, evaluation) =
+            zk_sum_check::prove_zk(params, &poly, &claim, &mut transcript, rng);
+
+        let weights = split_point_weights(layer, &sumcheck_point, 1);
+        let o_1 = Opening::random(inner_product(w, &weights.0), rng);
+        let o_2 = Opening::random(inner_product(w, &weights.1), rng);
+        let product = Opening::random(o_1.value * o_2.value, rng);
+
+        let evaluations = [o_1, o_2, product].map(|opening| params.commit_opening(&opening));
+        transcript.append(&points_to_bytes(&evaluations));
+
+        let product_proof = ProductProof::prove(params, &o_1, &o_2, &product, &mut transcript, rng);
+
+        let (a_r, m_r) = get_weighted_wiring(layer, &a_weights, &weights.0, &weights.1);
+        let expected = Opening {
+            value: a_r * (o_1.value + o_2.value) + m_r * product.value,
+            blind: a_r * (o_1.blind + o_2.blind) + m_r * product.blind,
+        };
+        let layer_proof =
+            EqualityProof::prove(params, &evaluation, &expected, &mut transcript, rng);
+
+        let alpha = transcript.get_random_challenge();
+        let beta = transcript.get_random_challenge();
+        claim = Opening {
+            value: alpha * o_1.value + beta * o_2.value,
+            blind: alpha * o_1.blind + beta * o_2.blind,
+        };
+        a_weights = combine_weights(&weights, alpha, beta);
+
+        layer_proofs.push(ZkLayerProof {
+            sumcheck_proof,
+            evaluations,
+            product_proof,
+            layer_proof,
+        });
+    }
+
+    let input_proof = DotProductProof::prove(
+        params,
+        layer_inputs.last().unwrap(),
+        input_blind,
+        &a_weights,
+        &claim,
+        &mut transcript,
+        rng,
+    );
+
+    let proof = ZkProof {
+        layer_proofs,
+        input_proof,
+    };
+
+    Ok((outputs, proof))
+}
+
+pub fn verify_zk<C: SWCurveConfig>(
+    params: &PedersenParams<C>,
+    proof: &ZkProof<C>,
+    circuit: &Circuit<C::ScalarField>,
+    input_commitment: &Projective<C>,
+    claimed_outputs: &[C::ScalarField],
+) -> Result<(), VerifyError> {
+    let num_layers = circuit.layers.len();
+    if num_layers == 0 {
+        return Err(VerifyError::EmptyCircuit);
+    }
+
+    if let Some(layer) = misshapen_layer(circuit) {
+        return Err(VerifyError::MisshapenLayer { layer });
+    }
+
+    if proof.layer_proofs.len() != num_layers {
+        return Err(VerifyError::WrongNumberOfLayers {
+            expected: num_layers,
+            got: proof.layer_proofs.len(),
+        });
+    }
+
+    let expected = layer_from_output(circuit, 0).num_gates();
+    if claimed_outputs.len() != expected {
+        return Err(VerifyError::WrongNumberOfOutputs {
+            expected,
+            got: claimed_outputs.len(),
+        });
+    }
+
+    let mut transcript = Transcript::new();
+    let (output_point, output_claim) =
+        absorb_outputs(circuit, input_commitment, claimed_outputs, &mut transcript);
+
+    let mut claim = public_commitment(params, output_claim);
+    let mut a_weights = point_weights(&output_point, 1);
+
+    for (layer_index, layer_proof) in proof.layer_proofs.iter().enumerate() {
+        let layer = layer_from_output(circuit, layer_index);
+
+        let sumcheck_claim = zk_sum_check::verify_zk(
+            params,
+            &layer_proof.sumcheck_proof,
+            &claim,
+            2 * layer.input_bits(),
+            2,
+            &mut transcript,
+        )
+        .map_err(|error| VerifyError::Sumcheck {
+            layer: layer_index,
+            error,
+        })?;
+
+        let [o_1, o_2, product] = layer_proof.evaluations;
+        transcript.append(&points_to_bytes(&layer_proof.evaluations));
+
+        let weights = split_point_weights(layer, &sumcheck_claim.point, 1);
+        let (a_r, m_r) = get_weighted_wiring(layer, &a_weights, &weights.0, &weights.1);
+        let expected = (o_1 + o_2) * a_r + product * m_r;
+
+        if !layer_proof
+            .product_proof
+            .verify(params, &o_1, &o_2, &product, &mut transcript)
+            || !layer_proof.layer_proof.verify(
+                params,
+                &sumcheck_claim.commitment,
+                &expected,
+                &mut transcript,
+            )
+        {
+            return Err(VerifyError::LayerMismatch { layer: layer_index });
+        }
+
+        let alpha = transcript.get_random_challenge();
+        let beta = transcript.get_random_challenge();
+        claim = o_1 * alpha + o_2 * beta;
+        a_weights = combine_weights(&weights, alpha, beta);
+    }
+
+    if !proof.input_proof.verify(
+        params,
+        input_commitment,
+        &claim,
+        &a_weights,
+        &mut transcript,
+    ) {
+        return Err(VerifyError::InputMismatch);
+    }
+
+    Ok(())
 }
 
 pub fn tensor_add_mul_polynomials<F: PrimeField>(
@@ -240,6 +1223,13 @@ pub fn tensor_add_mul_polynomials<F: PrimeField>(
     poly_b: &[F],
     op: Operation,
 ) -> MultilinearPoly<F> {
+    #[cfg(feature = "parallel")]
+    let new_eval: Vec<F> = poly_a
+        .par_iter()
+        .flat_map_iter(|a| poly_b.iter().map(move |b| op.apply(*a, *b)))
+        .collect();
+
+    #[cfg(not(feature = "parallel"))]
     let new_eval: Vec<F> = poly_a
         .iter()
         .flat_map(|a| poly_b.iter().map(move |b| op.apply(*a, *b)))
@@ -248,18 +1238,19 @@ pub fn tensor_add_mul_polynomials<F: PrimeField>(
     MultilinearPoly::new(new_eval)
 }
 
+// output_point fixes the gate bits a of the output layer, one challenge per output bit
 pub fn get_fbc_poly<F: PrimeField>(
-    random_challenge: F,
+    output_point: &[F],
     layer: Layer<F>,
     w_b: &[F],
     w_c: &[F],
 ) -> SumPoly<F> {
     let add_i = layer
         .get_add_mul_i(Operation::Add)
-        .partial_evaluate(0, &random_challenge);
+        .multi_partial_evaluate(output_point);
     let mul_i = layer
         .get_add_mul_i(Operation::Mul)
-        .partial_evaluate(0, &random_challenge);
+        .multi_partial_evaluate(output_point);
 
     let summed_w_poly = tensor_add_mul_polynomials(w_b, w_c, Operation::Add);
     let multiplied_w_poly = tensor_add_mul_polynomials(w_b, w_c, Operation::Mul);
@@ -270,105 +1261,86 @@ pub fn get_fbc_poly<F: PrimeField>(
     SumPoly::new(vec![add_eval_product, mul_eval_product])
 }
 
-fn get_folded_fbc_poly<F: PrimeField>(
-    layer: Layer<F>,
-    w_b: &[F],
-    w_c: &[F],
-    r_b: &[F],
-    r_c: &[F],
-    alpha: F,
-    beta: F,
-) -> SumPoly<F> {
-    let add_i = layer.get_add_mul_i(Operation::Add);
-    let mul_i = layer.get_add_mul_i(Operation::Mul);
-
-    let summed_add_i = add_i.multi_partial_evaluate(r_b).scale(alpha)
-        + add_i.multi_partial_evaluate(r_c).scale(beta);
-
-    let summed_mul_i = mul_i.multi_partial_evaluate(r_b).scale(alpha)
-        + mul_i.multi_partial_evaluate(r_c).scale(beta);
-
-    let summed_w_poly = tensor_add_mul_polynomials(w_b, w_c, Operation::Add);
-    let multiplied_w_poly = tensor_add_mul_polynomials(w_b, w_c, Operation::Mul);
-
-    let add_product_poly = ProductPoly::new(vec![summed_add_i.evals, summed_w_poly.evals]);
-    let mul_product_poly = ProductPoly::new(vec![summed_mul_i.evals, multiplied_w_poly.evals]);
-
-    SumPoly::new(vec![add_product_poly, mul_product_poly])
-}
-
-fn get_verifier_claim<F: PrimeField>(
+// add_i and mul_i with the gate bits a summed against a_weights, as tables over b || c.
+// eq(r) weights give add_i(r, b, c), the sparse wiring keeps this at one pass over the gates
+fn get_weighted_add_mul_i<F: PrimeField>(
     layer: &Layer<F>,
-    init_random_challenge: F,
-    sumcheck_random_challenges: &[F],
-    o_1: F,
-    o_2: F,
-) -> F {
-    let mut all_random_challenges = Vec::with_capacity(1 + sumcheck_random_challenges.len());
-
-    all_random_challenges.push(init_random_challenge);
-    all_random_challenges.extend_from_slice(sumcheck_random_challenges);
+    op: Operation,
+    a_weights: &[F],
+) -> MultilinearPoly<F> {
+    let bc_bits = 2 * layer.input_bits();
+    let mut evals = vec![F::zero(); 1 << bc_bits];
 
-    let a_r = layer
-        .get_add_mul_i(Operation::Add)
-        .evaluate(all_random_challenges.clone());
-    let m_r = layer
-        .get_add_mul_i(Operation::Mul)
-        .evaluate(all_random_challenges);
+    for (index, value) in layer.get_sparse_add_mul_i(op).evals {
+        evals[index & ((1 << bc_bits) - 1)] += a_weights[index >> bc_bits] * value;
+    }
 
-    (a_r * (o_1 + o_2)) + (m_r * (o_1 * o_2))
+    MultilinearPoly::new(evals)
 }
 
-fn get_folded_verifier_claim<F: PrimeField>(
-    layer: &Layer<F>,
-    current_random_challenge: &[F],
-    previous_random_challenge: &[F],
-    o_1: F,
-    o_2: F,
-    alpha: F,
-    beta: F,
-) -> F {
-    let (prev_r_b, prev_r_c) =
-        previous_random_challenge.split_at(previous_random_challenge.len() / 2);
+// get_fbc_poly with the output point, or alpha * r_b + beta * r_c of the layer above, given
+// as weights over a
+fn get_weighted_fbc_poly<F: PrimeField>(layer: &Layer<F>, w: &[F], a_weights: &[F]) -> SumPoly<F> {
+    let add_i = get_weighted_add_mul_i(layer, Operation::Add, a_weights);
+    let mul_i = get_weighted_add_mul_i(layer, Operation::Mul, a_weights);
 
-    let add_i = layer.get_add_mul_i(Operation::Add);
-    let mul_i = layer.get_add_mul_i(Operation::Mul);
+    let summed_w_poly = tensor_add_mul_polynomials(w, w, Operation::Add);
+    let multiplied_w_poly = tensor_add_mul_polynomials(w, w, Operation::Mul);
 
-    let summed_add_i = add_i.multi_partial_evaluate(prev_r_b).scale(alpha)
-        + add_i.multi_partial_evaluate(prev_r_c).scale(beta);
+    let add_product_poly = ProductPoly::new(vec![add_i.evals, summed_w_poly.evals]);
+    let mul_product_poly = ProductPoly::new(vec![mul_i.evals, multiplied_w_poly.evals]);
 
-    let summed_mul_i = mul_i.multi_partial_evaluate(prev_r_b).scale(alpha)
-        + mul_i.multi_partial_evaluate(prev_r_c).scale(beta);
-
-    let a_r = summed_add_i.evaluate(current_random_challenge.to_vec());
-    let m_r = summed_mul_i.evaluate(current_random_challenge.to_vec());
-
-    (a_r * (o_1 + o_2)) + (m_r * (o_1 * o_2))
+    SumPoly::new(vec![add_product_poly, mul_product_poly])
 }
 
-fn evaluate_input_poly<F: PrimeField>(inputs: &[F], sumcheck_random_challenges: &[F]) -> (F, F) {
-    let input_poly = MultilinearPoly::new(inputs.to_vec());
-
-    let (r_b, r_c) = sumcheck_random_challenges.split_at(sumcheck_random_challenges.len() / 2);
-
-    let o_1 = input_poly.evaluate(r_b.to_vec());
-    let o_2 = input_poly.evaluate(r_c.to_vec());
+// add_i and mul_i with every block of gate bits summed against its weights, the claim is
+// then add_i * (o_1 + o_2) + mul_i * o_1 * o_2
+fn get_weighted_wiring<F: PrimeField>(
+    layer: &Layer<F>,
+    a_weights: &[F],
+    b_weights: &[F],
+    c_weights: &[F],
+) -> (F, F) {
+    let input_bits = layer.input_bits();
+    let mask = (1 << input_bits) - 1;
+
+    let evaluate = |op: Operation| -> F {
+        layer
+            .get_sparse_add_mul_i(op)
+            .evals
+            .iter()
+            .map(|(index, value)| {
+                *value
+                    * a_weights[index >> (2 * input_bits)]
+                    * b_weights[(index >> input_bits) & mask]
+                    * c_weights[index & mask]
+            })
+            .sum()
+    };
 
-    (o_1, o_2)
+    (evaluate(Operation::Add), evaluate(Operation::Mul))
 }
 
 #[cfg(test)]
 mod test {
+    use crate::libs::sum_check_protocol::{CompressedSumcheckProof, SumcheckError};
     use crate::libs::{gkr_circuits_impl, multilinear_poly};
 
     use super::{
-        get_fbc_poly, get_folded_fbc_poly, prove, tensor_add_mul_polynomials, verify, Proof,
+        commit_inputs, get_fbc_poly, inner_product, prove, prove_with_univariate_skip, prove_zk,
+        replay, tensor_add_mul_polynomials, verify, verify_with_univariate_skip, verify_zk,
+        zk_params_len, GkrMessage, GkrProver, GkrVerifier, Proof, ProveError, VerifyError,
     };
-    use ark_bn254::Fq;
+    use crate::libs::pedersen::PedersenParams;
+    use ark_bn254::{g1::Config, Fq, Fr};
     use gkr_circuits_impl::{Circuit, Gate, Layer, Operation};
     use multilinear_poly::{
         MultilinearPoly, {ProductPoly, SumPoly},
     };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::mpsc;
+    use std::thread;
 
     #[test]
     fn it_add_polys_correctly() {
@@ -445,7 +1417,7 @@ mod test {
 
         let mul_i_r = MultilinearPoly::new(vec![Fq::from(0); 4]);
 
-        let fbc_poly = get_fbc_poly(r_c, layer, w_1_poly, w_1_poly);
+        let fbc_poly = get_fbc_poly(&[r_c], layer, w_1_poly, w_1_poly);
 
         let one = ProductPoly::new(vec![
             add_i_r.evals,
@@ -486,13 +1458,323 @@ mod test {
             Fq::from(3),
         ];
 
-        let mut circuit = Circuit::new(circuit_structure);
+        let circuit = Circuit::new(circuit_structure);
+
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+        assert_eq!(outputs, vec![Fq::from(27)]);
+        assert_eq!(verify(&proof, &circuit, &inputs, &outputs), Ok(()));
+
+        // the same circuit proves other inputs
+        let other_inputs: Vec<Fq> = inputs.iter().map(|input| *input + Fq::from(1)).collect();
+        let (other_outputs, other_proof) = prove(&circuit, &other_inputs).unwrap();
+
+        assert_eq!(
+            verify(&other_proof, &circuit, &other_inputs, &other_outputs),
+            Ok(())
+        );
+        assert_eq!(
+            verify(&other_proof, &circuit, &other_inputs, &outputs),
+            Err(VerifyError::ClaimMismatch { layer: 0 })
+        );
+    }
+
+    #[test]
+    fn it_proves_circuits_with_several_outputs() {
+        // three outputs, so the output layer is padded to four gates
+        let circuit_structure: Vec<Vec<Operation>> = vec![
+            vec![
+                Operation::Mul,
+                Operation::Mul,
+                Operation::Add,
+                Operation::Mul,
+                Operation::Add,
+                Operation::Mul,
+            ],
+            vec![Operation::Add, Operation::Mul, Operation::Add],
+        ];
+
+        let inputs: Vec<Fq> = (1..=12).map(Fq::from).collect();
 
-        let proof = prove(&mut circuit, &inputs);
+        let circuit = Circuit::new(circuit_structure);
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+        // (1 * 2) + (3 * 4), (5 + 6) * (7 * 8), (9 + 10) + (11 * 12)
+        assert_eq!(outputs, vec![Fq::from(14), Fq::from(616), Fq::from(151)]);
+        assert_eq!(verify(&proof, &circuit, &inputs, &outputs), Ok(()));
 
-        let is_verified = verify(proof, circuit, &inputs);
+        for i in 0..outputs.len() {
+            let mut other_outputs = outputs.clone();
+            other_outputs[i] += Fq::from(1);
+
+            assert!(verify(&proof, &circuit, &inputs, &other_outputs).is_err());
+        }
 
-        assert_eq!(is_verified, true);
+        assert_eq!(
+            verify(&proof, &circuit, &inputs, &outputs[..2]),
+            Err(VerifyError::WrongNumberOfOutputs {
+                expected: 3,
+                got: 2
+            })
+        );
+
+        // a padding output of zero is still not one of the circuit's outputs
+        let mut padded_outputs = outputs.clone();
+        padded_outputs.push(Fq::from(0));
+        assert_eq!(
+            verify(&proof, &circuit, &inputs, &padded_outputs),
+            Err(VerifyError::WrongNumberOfOutputs {
+                expected: 3,
+                got: 4
+            })
+        );
+
+        assert_eq!(
+            verify(&proof, &circuit, &inputs[..11], &outputs),
+            Err(VerifyError::WrongNumberOfInputs {
+                expected: 12,
+                got: 11
+            })
+        );
+    }
+
+    #[test]
+    fn it_rejects_inputs_moved_where_the_input_layer_does_not_look() {
+        let circuit = Circuit::new(vec![
+            vec![Operation::Mul, Operation::Mul],
+            vec![Operation::Add],
+        ]);
+        let inputs: Vec<Fq> = (2..=5).map(Fq::from).collect();
+
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+        // (2 * 3) + (4 * 5)
+        assert_eq!(outputs, vec![Fq::from(26)]);
+
+        // the input layer reads the inputs through these weights only, so any direction
+        // both of them zero leaves o_1, o_2 and the last check where they were
+        let (w_b, w_c) = replay(&proof, &circuit, &inputs, &outputs, 1)
+            .unwrap()
+            .weights;
+        let det = w_b[0] * w_c[1] - w_b[1] * w_c[0];
+        let direction = vec![
+            (w_b[1] * w_c[2] - w_b[2] * w_c[1]) / det,
+            (w_b[2] * w_c[0] - w_b[0] * w_c[2]) / det,
+            Fq::from(1),
+            Fq::from(0),
+        ];
+
+        assert_eq!(inner_product(&direction, &w_b), Fq::from(0));
+        assert_eq!(inner_product(&direction, &w_c), Fq::from(0));
+
+        let forged: Vec<Fq> = inputs
+            .iter()
+            .zip(direction.iter())
+            .map(|(input, step)| *input + step)
+            .collect();
+
+        assert_ne!(circuit.evaluations(&forged).last().unwrap(), &outputs);
+        assert!(verify(&proof, &circuit, &forged, &outputs).is_err());
+    }
+
+    #[test]
+    fn it_verifies_proofs_in_parallel_against_one_circuit() {
+        let (circuit, inputs) = small_circuit();
+
+        let proofs: Vec<_> = (0..4u64)
+            .map(|shift| {
+                let inputs: Vec<Fq> = inputs
+                    .iter()
+                    .map(|input| *input + Fq::from(shift))
+                    .collect();
+                let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+                (inputs, outputs, proof)
+            })
+            .collect();
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = proofs
+                .iter()
+                .map(|(inputs, outputs, proof)| {
+                    scope.spawn(|| verify(proof, &circuit, inputs, outputs))
+                })
+                .collect();
+
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), Ok(()));
+            }
+        });
+    }
+
+    #[test]
+    fn it_rejects_malformed_round_polys() {
+        let circuit_structure: Vec<Vec<Operation>> =
+            vec![vec![Operation::Mul, Operation::Mul], vec![Operation::Add]];
+
+        let inputs: Vec<Fq> = vec![Fq::from(1), Fq::from(2), Fq::from(3), Fq::from(4)];
+
+        let circuit = Circuit::new(circuit_structure);
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+        let mut short_round = proof.clone();
+        short_round.sumcheck_proofs[0].round_polys[0].truncate(1);
+
+        let mut missing_round = proof.clone();
+        missing_round.sumcheck_proofs[1].round_polys.pop();
+
+        let mut missing_layer = proof;
+        missing_layer.sumcheck_proofs.pop();
+
+        assert_eq!(
+            verify(&short_round, &circuit, &inputs, &outputs),
+            Err(VerifyError::Sumcheck {
+                layer: 0,
+                error: SumcheckError::WrongRoundPolyLength {
+                    round: 0,
+                    expected: 2,
+                    got: 1
+                }
+            })
+        );
+        assert_eq!(
+            verify(&missing_round, &circuit, &inputs, &outputs),
+            Err(VerifyError::Incomplete)
+        );
+        assert_eq!(
+            verify(&missing_layer, &circuit, &inputs, &outputs),
+            Err(VerifyError::WrongNumberOfLayers {
+                expected: 2,
+                got: 1
+            })
+        );
+    }
+
+    #[test]
+    fn it_proves_layers_with_a_univariate_skip() {
+        let circuit_structure: Vec<Vec<Operation>> = vec![
+            vec![
+                Operation::Mul,
+                Operation::Add,
+                Operation::Mul,
+                Operation::Mul,
+            ],
+            vec![Operation::Add, Operation::Mul],
+            vec![Operation::Add],
+        ];
+
+        let inputs: Vec<Fq> = (1..=8).map(Fq::from).collect();
+
+        let circuit = Circuit::new(circuit_structure);
+        let (outputs, plain) = prove(&circuit, &inputs).unwrap();
+        let (skip_outputs, proof) = prove_with_univariate_skip(&circuit, &inputs, 2).unwrap();
+
+        assert_eq!(outputs, skip_outputs);
+        assert_eq!(
+            verify_with_univariate_skip(&proof, &circuit, &inputs, &outputs, 2),
+            Ok(())
+        );
+
+        // the output layer reads one bit, so it runs as before. below it the first two
+        // bits of b are one round of degree 2 * 3, six values once compressed
+        let rounds = |proof: &Proof<Fq>| -> Vec<usize> {
+            proof
+                .sumcheck_proofs
+                .iter()
+                .map(|proof| proof.round_polys.len())
+                .collect()
+        };
+        assert_eq!(rounds(&plain), vec![2, 4, 6]);
+        assert_eq!(rounds(&proof), vec![2, 3, 5]);
+        assert_eq!(proof.sumcheck_proofs[2].round_polys[0].len(), 6);
+
+        let mut other_outputs = outputs.clone();
+        other_outputs[0] += Fq::from(1);
+        assert!(verify_with_univariate_skip(&proof, &circuit, &inputs, &other_outputs, 2).is_err());
+
+        // the skip is part of the statement, a proof only verifies with its own
+        assert!(verify(&proof, &circuit, &inputs, &outputs).is_err());
+        assert!(verify_with_univariate_skip(&plain, &circuit, &inputs, &outputs, 2).is_err());
+    }
+
+    #[test]
+    fn it_rejects_a_circuit_without_layers() {
+        let circuit = Circuit::<Fq>::new(vec![]);
+        let proof = Proof {
+            sumcheck_proofs: vec![],
+            claimed_evaluations: vec![],
+        };
+
+        assert_eq!(
+            verify(&proof, &circuit, &[Fq::from(1)], &[Fq::from(1)]),
+            Err(VerifyError::EmptyCircuit)
+        );
+        assert_eq!(
+            GkrVerifier::new(&circuit, &[]).receive(GkrMessage::Outputs(vec![])),
+            Err(VerifyError::EmptyCircuit)
+        );
+        assert_eq!(
+            prove(&circuit, &[Fq::from(1)]).unwrap_err(),
+            ProveError::EmptyCircuit
+        );
+    }
+
+    #[test]
+    fn it_rejects_the_wrong_number_of_inputs() {
+        let (circuit, inputs) = small_circuit();
+
+        assert_eq!(
+            prove(&circuit, &inputs[..3]).unwrap_err(),
+            ProveError::WrongNumberOfInputs {
+                expected: 4,
+                got: 3
+            }
+        );
+
+        // an extra input would not be read by any gate
+        let mut extra_inputs = inputs.clone();
+        extra_inputs.push(Fq::from(5));
+        assert_eq!(
+            prove(&circuit, &extra_inputs).unwrap_err(),
+            ProveError::WrongNumberOfInputs {
+                expected: 4,
+                got: 5
+            }
+        );
+    }
+
+    #[test]
+    fn it_rejects_a_layer_that_is_not_half_as_wide() {
+        // Circuit::new refuses this shape, the layers can still be put together by hand
+        let layers = vec![
+            Layer::new(vec![
+                Gate::new(Fq::from(0), Fq::from(0), Operation::Mul),
+                Gate::new(Fq::from(0), Fq::from(0), Operation::Mul),
+                Gate::new(Fq::from(0), Fq::from(0), Operation::Add),
+                Gate::new(Fq::from(0), Fq::from(0), Operation::Add),
+            ]),
+            Layer::new(vec![Gate::new(Fq::from(0), Fq::from(0), Operation::Add)]),
+        ];
+        let circuit = Circuit { layers };
+        let inputs: Vec<Fq> = (1..=8).map(Fq::from).collect();
+
+        assert_eq!(
+            prove(&circuit, &inputs).unwrap_err(),
+            ProveError::MisshapenLayer { layer: 0 }
+        );
+
+        let proof = Proof {
+            sumcheck_proofs: vec![],
+            claimed_evaluations: vec![],
+        };
+        assert_eq!(
+            verify(&proof, &circuit, &inputs, &[Fq::from(1)]),
+            Err(VerifyError::MisshapenLayer { layer: 0 })
+        );
+        assert_eq!(
+            GkrVerifier::new(&circuit, &inputs).receive(GkrMessage::Outputs(vec![Fq::from(1)])),
+            Err(VerifyError::MisshapenLayer { layer: 0 })
+        );
     }
 
     #[test]
@@ -512,21 +1794,243 @@ mod test {
         let dummy_proof_poly_6 = vec![Fq::from(10), Fq::from(5)];
 
         let invalid_proof = Proof {
-            output_poly: MultilinearPoly::new(vec![Fq::from(10), Fq::from(0)]),
-            proof_polynomials: vec![
-                vec![dummy_proof_poly_1, dummy_proof_poly_2],
-                vec![
-                    dummy_proof_poly_3,
-                    dummy_proof_poly_4,
-                    dummy_proof_poly_5,
-                    dummy_proof_poly_6,
-                ],
+            sumcheck_proofs: vec![
+                CompressedSumcheckProof {
+                    claimed_sum: Fq::from(15),
+                    round_polys: vec![dummy_proof_poly_1, dummy_proof_poly_2],
+                },
+                CompressedSumcheckProof {
+                    claimed_sum: Fq::from(15),
+                    round_polys: vec![
+                        dummy_proof_poly_3,
+                        dummy_proof_poly_4,
+                        dummy_proof_poly_5,
+                        dummy_proof_poly_6,
+                    ],
+                },
             ],
             claimed_evaluations: vec![(Fq::from(10), Fq::from(5))],
         };
 
-        let is_verified = verify(invalid_proof, circuit, &inputs);
+        let result = verify(&invalid_proof, &circuit, &inputs, &[Fq::from(10)]);
+
+        assert!(result.is_err());
+    }
+
+    fn small_circuit() -> (Circuit<Fq>, Vec<Fq>) {
+        let circuit_structure: Vec<Vec<Operation>> =
+            vec![vec![Operation::Mul, Operation::Mul], vec![Operation::Add]];
+
+        let inputs: Vec<Fq> = vec![Fq::from(1), Fq::from(2), Fq::from(3), Fq::from(4)];
+
+        (Circuit::new(circuit_structure), inputs)
+    }
+
+    #[test]
+    fn it_runs_interactively_across_a_channel() {
+        let (circuit, inputs) = small_circuit();
+        let (message_tx, message_rx) = mpsc::channel();
+        let (challenge_tx, challenge_rx) = mpsc::channel();
+
+        let prover_circuit = circuit.clone();
+        let prover_inputs = inputs.clone();
+        let prover = thread::spawn(move || {
+            let mut prover = GkrProver::new(&prover_circuit, &prover_inputs);
+
+            while !prover.is_done() {
+                if prover.wants_challenge() {
+                    prover.receive_challenge(challenge_rx.recv().unwrap());
+                } else {
+                    message_tx.send(prover.next_message()).unwrap();
+                }
+            }
+        });
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut verifier = GkrVerifier::new(&circuit, &inputs);
+
+        // the prover hangs up once it has its last challenge
+        for message in message_rx {
+            verifier.receive(message).unwrap();
+
+            while verifier.wants_challenge() {
+                challenge_tx
+                    .send(verifier.sample_challenge(&mut rng))
+                    .unwrap();
+            }
+        }
+
+        prover.join().unwrap();
+        assert_eq!(verifier.finish(), Ok(()));
+    }
 
-        assert_eq!(is_verified, false);
+    #[test]
+    fn it_runs_interactively_with_several_outputs() {
+        // four outputs, so the output point takes two challenges
+        let circuit = Circuit::new(vec![vec![Operation::Mul; 8], vec![Operation::Add; 4]]);
+        let inputs: Vec<Fq> = (1..=16).map(Fq::from).collect();
+
+        let run = |lie: Fq| -> Result<(), VerifyError> {
+            let mut prover = GkrProver::new(&circuit, &inputs);
+            let mut verifier = GkrVerifier::new(&circuit, &inputs);
+            let mut rng = StdRng::seed_from_u64(42);
+
+            while !prover.is_done() {
+                if prover.wants_challenge() {
+                    prover.receive_challenge(verifier.sample_challenge(&mut rng));
+                    continue;
+                }
+
+                let message = match prover.next_message() {
+                    GkrMessage::Outputs(mut outputs) => {
+                        // (1 * 2) + (3 * 4), ..., (13 * 14) + (15 * 16)
+                        assert_eq!(
+                            outputs,
+                            vec![Fq::from(14), Fq::from(86), Fq::from(222), Fq::from(422)]
+                        );
+
+                        outputs[3] += lie;
+                        GkrMessage::Outputs(outputs)
+                    }
+                    message => message,
+                };
+
+                verifier.receive(message)?;
+            }
+
+            verifier.finish()
+        };
+
+        assert_eq!(run(Fq::from(0)), Ok(()));
+        assert!(run(Fq::from(1)).is_err());
+
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+        assert_eq!(verify(&proof, &circuit, &inputs, &outputs), Ok(()));
+    }
+
+    #[test]
+    fn it_rejects_an_interactive_prover_lying_about_outputs() {
+        let (circuit, inputs) = small_circuit();
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let mut prover = GkrProver::new(&circuit, &inputs);
+        let mut verifier = GkrVerifier::new(&circuit, &inputs);
+
+        let mut result = Ok(());
+        while !prover.is_done() && result.is_ok() {
+            if prover.wants_challenge() {
+                prover.receive_challenge(verifier.sample_challenge(&mut rng));
+                continue;
+            }
+
+            // (1 * 2) + (3 * 4) = 14, the prover claims 15
+            let message = match prover.next_message() {
+                GkrMessage::Outputs(mut outputs) => {
+                    outputs[0] += Fq::from(1);
+                    GkrMessage::Outputs(outputs)
+                }
+                message => message,
+            };
+
+            result = verifier.receive(message);
+        }
+
+        // compressed rounds always add up, so the lie shows in the layer evaluations
+        assert_eq!(result, Err(VerifyError::LayerMismatch { layer: 0 }));
+
+        let mut verifier = GkrVerifier::new(&circuit, &inputs);
+        assert_eq!(
+            verifier.receive(GkrMessage::Evaluations(Fq::from(1), Fq::from(2))),
+            Err(VerifyError::UnexpectedMessage)
+        );
+        assert_eq!(verifier.finish(), Err(VerifyError::Incomplete));
+    }
+
+    #[test]
+    fn it_proves_a_circuit_in_zero_knowledge() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for structure in [
+            vec![vec![Operation::Mul; 8], vec![Operation::Add; 4]],
+            vec![
+                vec![
+                    Operation::Add,
+                    Operation::Mul,
+                    Operation::Add,
+                    Operation::Mul,
+                ],
+                vec![Operation::Mul, Operation::Add],
+                vec![Operation::Mul],
+            ],
+        ] {
+            let circuit = Circuit::<Fr>::new(structure);
+            let inputs: Vec<Fr> = (1..=2 * circuit.layers[0].num_gates() as u64)
+                .map(Fr::from)
+                .collect();
+            let params = PedersenParams::<Config>::new(zk_params_len(&circuit), b"gkr tests");
+
+            let (input_commitment, input_blind) =
+                commit_inputs(&params, &circuit, &inputs, &mut rng);
+            let (outputs, proof) =
+                prove_zk(&params, &circuit, &inputs, input_blind, &mut rng).unwrap();
+
+            assert_eq!(outputs, circuit.evaluations(&inputs).pop().unwrap());
+            assert_eq!(
+                verify_zk(&params, &proof, &circuit, &input_commitment, &outputs),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_zero_knowledge_proof() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let circuit = Circuit::<Fr>::new(vec![vec![Operation::Mul; 8], vec![Operation::Add; 4]]);
+        let inputs: Vec<Fr> = (1..=16).map(Fr::from).collect();
+        let params = PedersenParams::<Config>::new(zk_params_len(&circuit), b"gkr tests");
+
+        let (input_commitment, input_blind) = commit_inputs(&params, &circuit, &inputs, &mut rng);
+        let (outputs, proof) = prove_zk(&params, &circuit, &inputs, input_blind, &mut rng).unwrap();
+
+        let mut wrong_outputs = outputs.clone();
+        wrong_outputs[3] += Fr::from(1);
+        assert!(matches!(
+            verify_zk(&params, &proof, &circuit, &input_commitment, &wrong_outputs),
+            Err(VerifyError::Sumcheck { layer: 0, .. })
+        ));
+
+        // other inputs under the commitment
+        let other_inputs: Vec<Fr> = (2..=17).map(Fr::from).collect();
+        let (other_commitment, _) = commit_inputs(&params, &circuit, &other_inputs, &mut rng);
+        assert!(verify_zk(&params, &proof, &circuit, &other_commitment, &outputs).is_err());
+
+        let mut wrong_evaluation = proof.clone();
+        wrong_evaluation.layer_proofs[0].evaluations[0] += params.generators[0];
+        assert_eq!(
+            verify_zk(
+                &params,
+                &wrong_evaluation,
+                &circuit,
+                &input_commitment,
+                &outputs
+            ),
+            Err(VerifyError::LayerMismatch { layer: 0 })
+        );
+
+        let mut missing_layer = proof;
+        missing_layer.layer_proofs.pop();
+        assert_eq!(
+            verify_zk(
+                &params,
+                &missing_layer,
+                &circuit,
+                &input_commitment,
+                &outputs
+            ),
+            Err(VerifyError::WrongNumberOfLayers {
+                expected: 2,
+                got: 1
+            })
+        );
     }
 }
@@ -0,0 +1,57 @@
+use ark_ff::fields::{Fp2, Fp2Config, Fp64, MontBackend, MontConfig};
+use ark_ff::MontFp;
+
+// p = 2^64 - 2^32 + 1, so 64 bit witness values fit in one limb
+#[derive(MontConfig)]
+#[modulus = "18446744069414584321"]
+#[generator = "7"]
+pub struct GoldilocksConfig;
+pub type Goldilocks = Fp64<MontBackend<GoldilocksConfig, 1>>;
+
+// Goldilocks[X] / (X^2 - 7). 7 generates the multiplicative group, so it is not a square.
+// about 128 bits, enough for challenges where 64 bits would not be
+pub struct GoldilocksExt2Config;
+pub type GoldilocksExt2 = Fp2<GoldilocksExt2Config>;
+
+impl Fp2Config for GoldilocksExt2Config {
+    type Fp = Goldilocks;
+
+    const NONRESIDUE: Goldilocks = MontFp!("7");
+
+    // NONRESIDUE^((p^i - 1) / 2) for i = 0, 1
+    const FROBENIUS_COEFF_FP2_C1: &'static [Goldilocks] =
+        &[MontFp!("1"), MontFp!("18446744069414584320")];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Goldilocks, GoldilocksExt2};
+    use ark_ff::{Field, PrimeField};
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn goldilocks_wraps_at_the_modulus() {
+        let minus_one = Goldilocks::from(u64::MAX - (1 << 32) + 1);
+
+        assert_eq!(minus_one, -Goldilocks::from(1));
+        assert_eq!(minus_one + Goldilocks::from(1), Goldilocks::from(0));
+        assert_eq!(Goldilocks::MODULUS_BIT_SIZE, 64);
+    }
+
+    #[test]
+    fn extension_is_a_field() {
+        let mut rng = test_rng();
+        let x = GoldilocksExt2::rand(&mut rng);
+        let y = GoldilocksExt2::rand(&mut rng);
+
+        assert_eq!(x * x.inverse().unwrap(), GoldilocksExt2::ONE);
+        assert_eq!((x + y) * (x - y), x.square() - y.square());
+        assert_eq!(GoldilocksExt2::extension_degree(), 2);
+
+        // 7 is not a square in the base field but is one here
+        assert!(Goldilocks::from(7).sqrt().is_none());
+        assert!(GoldilocksExt2::from_base_prime_field(Goldilocks::from(7))
+            .sqrt()
+            .is_some());
+    }
+}
@@ -0,0 +1,259 @@
+use super::fiat_shamir::{fq_vec_to_bytes, Transcript};
+use super::multilinear_poly::{eq_eval, MultilinearPoly};
+use super::sum_check_protocol::{
+    CompressedSumcheckProof, EvaluationClaim, SumcheckError, SumcheckProver, SumcheckVerifier,
+};
+use super::virtual_poly::VirtualPoly;
+use ark_ff::PrimeField;
+use std::fmt;
+
+// proves prod v_i = product as a binary tree of multiplications. layer k has 2^k values and
+// V_k(x) = V_{k + 1}(0, x) * V_{k + 1}(1, x), so a claim on V_k at r becomes the sumcheck
+// of eq(r, x) * V_{k + 1}(0, x) * V_{k + 1}(1, x). the prover sends both halves at the
+// sumcheck point and a random line between them gives the claim on V_{k + 1}
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrandProductProof<F: PrimeField> {
+    pub product: F,
+    // layer k = 1, ..., n - 1, the root needs no sumcheck
+    pub layer_proofs: Vec<CompressedSumcheckProof<F>>,
+    // V_{k + 1}(0, r) and V_{k + 1}(1, r) for layer k = 0, ..., n - 1
+    pub layer_evaluations: Vec<(F, F)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GrandProductError {
+    WrongNumberOfLayers { expected: usize, got: usize },
+    ClaimMismatch { layer: usize },
+    Sumcheck { layer: usize, error: SumcheckError },
+    LayerMismatch { layer: usize },
+}
+
+impl fmt::Display for GrandProductError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrandProductError::WrongNumberOfLayers { expected, got } => {
+                write!(f, "expected {} layers, got {}", expected, got)
+            }
+            GrandProductError::ClaimMismatch { layer } => {
+                write!(f, "layer {} sumcheck does not start from the claim", layer)
+            }
+            GrandProductError::Sumcheck { layer, error } => {
+                write!(f, "layer {}: {}", layer, error)
+            }
+            GrandProductError::LayerMismatch { layer } => {
+                write!(f, "layer {} halves do not multiply to the claim", layer)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GrandProductError {}
+
+// layers[k] holds the 2^k values of layer k, the last one is the input
+fn product_layers<F: PrimeField>(values: &[F]) -> Vec<Vec<F>> {
+    let mut layers = vec![values.to_vec()];
+
+    while layers[0].len() > 1 {
+        let (left, right) = layers[0].split_at(layers[0].len() / 2);
+        let layer = left.iter().zip(right.iter()).map(|(l, r)| *l * r).collect();
+        layers.insert(0, layer);
+    }
+
+    layers
+}
+
+// absorbs the halves and moves the claim to V_{k + 1}(tau, r)
+fn reduce_to_line<F: PrimeField>(
+    point: &[F],
+    (left, right): (F, F),
+    transcript: &mut Transcript<F>,
+) -> (Vec<F>, F) {
+    transcript.append(&fq_vec_to_bytes(&[left, right]));
+    let tau = transcript.get_random_challenge();
+
+    ([&[tau], point].concat(), left + tau * (right - left))
+}
+
+// the returned claim is on the input poly, which the caller still has to check against
+// whatever the values are committed with
+pub fn prove<F: PrimeField>(
+    values: &MultilinearPoly<F>,
+    transcript: &mut Transcript<F>,
+) -> (GrandProductProof<F>, EvaluationClaim<F>) {
+    let layers = product_layers(&values.evals);
+    let product = layers[0][0];
+
+    transcript.append(&fq_vec_to_bytes(&[product]));
+
+    let mut layer_proofs = Vec::with_capacity(values.n_vars.saturating_sub(1));
+    let mut layer_evaluations = Vec::with_capacity(values.n_vars);
+    let mut point = vec![];
+    let mut claim = product;
+
+    for k in 0..values.n_vars {
+        let (left, right) = layers[k + 1].split_at(1 << k);
+
+        let halves = if k == 0 {
+            (left[0], right[0])
+        } else {
+            let mut poly = VirtualPoly::new(k);
+            let eq = poly.add_table(MultilinearPoly::eq(&point));
+            let l = poly.add_table(MultilinearPoly::new(left.to_vec()));
+            let r = poly.add_table(MultilinearPoly::new(right.to_vec()));
+            poly.add_term(F::one(), vec![eq, l, r]);
+
+            let (proof, challenges, folded) =
+                SumcheckProver::prove_rounds(&poly, claim, transcript);
+
+            layer_proofs.push(proof.compress());
+            point = challenges;
+
+            (folded.tables[l].evals[0], folded.tables[r].evals[0])
+        };
+
+        layer_evaluations.push(halves);
+        (point, claim) = reduce_to_line(&point, halves, transcript);
+    }
+
+    let proof = GrandProductProof {
+        product,
+        layer_proofs,
+        layer_evaluations,
+    };
+
+    (
+        proof,
+        EvaluationClaim {
+            point,
+            value: claim,
+        },
+    )
+}
+
+pub fn verify<F: PrimeField>(
+    proof: &GrandProductProof<F>,
+    n_vars: usize,
+    transcript: &mut Transcript<F>,
+) -> Result<EvaluationClaim<F>, GrandProductError> {
+    for (expected, got) in [
+        (n_vars.saturating_sub(1), proof.layer_proofs.len()),
+        (n_vars, proof.layer_evaluations.len()),
+    ] {
+        if expected != got {
+            return Err(GrandProductError::WrongNumberOfLayers { expected, got });
+        }
+    }
+
+    transcript.append(&fq_vec_to_bytes(&[proof.product]));
+
+    let mut point = vec![];
+    let mut claim = proof.product;
+
+    for (k, (left, right)) in proof.layer_evaluations.iter().enumerate() {
+        // the root has no variables to sum over, the claim is the product itself
+        let (eq, expected) = if k == 0 {
+            (F::one(), claim)
+        } else {
+            let layer_proof = &proof.layer_proofs[k - 1];
+            if layer_proof.claimed_sum != claim {
+                return Err(GrandProductError::ClaimMismatch { layer: k });
+            }
+
+            let sumcheck_claim = SumcheckVerifier::verify_compressed(layer_proof, k, 3, transcript)
+                .map_err(|error| GrandProductError::Sumcheck { layer: k, error })?;
+
+            let eq = eq_eval(&point, &sumcheck_claim.point);
+            point = sumcheck_claim.point;
+
+            (eq, sumcheck_claim.value)
+        };
+
+        if eq * left * right != expected {
+            return Err(GrandProductError::LayerMismatch { layer: k });
+        }
+
+        (point, claim) = reduce_to_line(&point, (*left, *right), transcript);
+    }
+
+    Ok(EvaluationClaim {
+        point,
+        value: claim,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prove, verify, GrandProductError};
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use ark_bn254::Fr;
+
+    fn values() -> MultilinearPoly<Fr> {
+        MultilinearPoly::new(to_field(vec![3, 1, 4, 1, 5, 9, 2, 6]))
+    }
+
+    #[test]
+    fn it_proves_the_product() {
+        let values = values();
+
+        let (proof, prover_claim) = prove(&values, &mut Transcript::new());
+        assert_eq!(proof.product, Fr::from(6480));
+
+        let claim = verify(&proof, 3, &mut Transcript::new()).unwrap();
+
+        assert_eq!(claim, prover_claim);
+        assert!(claim.check(|point| values.evaluate(point.to_vec())));
+    }
+
+    #[test]
+    fn it_handles_one_and_two_values() {
+        for evals in [vec![7], vec![7, 3]] {
+            let values = MultilinearPoly::new(to_field(evals.clone()));
+
+            let (proof, _) = prove(&values, &mut Transcript::new());
+            let claim = verify(&proof, values.n_vars, &mut Transcript::new()).unwrap();
+
+            assert_eq!(proof.product, Fr::from(evals.iter().product::<u64>()));
+            assert!(claim.check(|point| values.evaluate(point.to_vec())));
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_product() {
+        let values = values();
+        let (proof, _) = prove(&values, &mut Transcript::new());
+
+        let mut wrong_product = proof.clone();
+        wrong_product.product = Fr::from(6481);
+        assert_eq!(
+            verify(&wrong_product, 3, &mut Transcript::new()),
+            Err(GrandProductError::LayerMismatch { layer: 0 })
+        );
+
+        let mut wrong_layer = proof.clone();
+        wrong_layer.layer_evaluations[2].0 += Fr::from(1);
+        assert_eq!(
+            verify(&wrong_layer, 3, &mut Transcript::new()),
+            Err(GrandProductError::LayerMismatch { layer: 2 })
+        );
+
+        // consistent layers for other values only fail at the input
+        let other = MultilinearPoly::new(to_field(vec![3, 1, 4, 1, 5, 9, 6, 2]));
+        let claim = verify(
+            &prove(&other, &mut Transcript::new()).0,
+            3,
+            &mut Transcript::new(),
+        )
+        .unwrap();
+        assert!(!claim.check(|point| values.evaluate(point.to_vec())));
+
+        assert_eq!(
+            verify(&proof, 2, &mut Transcript::new()),
+            Err(GrandProductError::WrongNumberOfLayers {
+                expected: 1,
+                got: 2
+            })
+        );
+    }
+}
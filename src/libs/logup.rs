@@ -0,0 +1,478 @@
+use super::fiat_shamir::{fq_vec_to_bytes, Transcript};
+use super::multilinear_poly::{eq_eval, MultilinearPoly};
+use super::sum_check_protocol::{
+    CompressedSumcheckProof, EvaluationClaim, SumcheckError, SumcheckProver, SumcheckVerifier,
+};
+use super::virtual_poly::VirtualPoly;
+use ark_ff::PrimeField;
+use std::collections::HashMap;
+use std::fmt;
+
+// every witness entry is in the table iff sum 1 / (gamma - w_i) = sum m_j / (gamma - t_j)
+// for a random gamma, with m_j how often t_j is looked up. each side is a sum of fractions
+// p / q, added up in a binary tree the same way grand_product multiplies:
+// P_k(x) = P_{k + 1}(0, x) * Q_{k + 1}(1, x) + P_{k + 1}(1, x) * Q_{k + 1}(0, x) and
+// Q_k(x) = Q_{k + 1}(0, x) * Q_{k + 1}(1, x). the two claims on a layer are batched with a
+// random lambda into one degree 3 sumcheck
+#[derive(Clone, Debug, PartialEq)]
+pub struct FractionalSumProof<F: PrimeField> {
+    // numerator and denominator of the whole sum
+    pub root: (F, F),
+    // layer k = 1, ..., n - 1
+    pub layer_proofs: Vec<CompressedSumcheckProof<F>>,
+    // P_{k + 1}(0, r), P_{k + 1}(1, r), Q_{k + 1}(0, r), Q_{k + 1}(1, r) for k = 0, ..., n - 1
+    pub layer_evaluations: Vec<[F; 4]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FractionalSumError {
+    WrongNumberOfLayers { expected: usize, got: usize },
+    ClaimMismatch { layer: usize },
+    Sumcheck { layer: usize, error: SumcheckError },
+    LayerMismatch { layer: usize },
+}
+
+impl fmt::Display for FractionalSumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FractionalSumError::WrongNumberOfLayers { expected, got } => {
+                write!(f, "expected {} layers, got {}", expected, got)
+            }
+            FractionalSumError::ClaimMismatch { layer } => {
+                write!(f, "layer {} sumcheck does not start from the claim", layer)
+            }
+            FractionalSumError::Sumcheck { layer, error } => {
+                write!(f, "layer {}: {}", layer, error)
+            }
+            FractionalSumError::LayerMismatch { layer } => {
+                write!(f, "layer {} halves do not add up to the claim", layer)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FractionalSumError {}
+
+// layers[k] holds the 2^k numerators and denominators of layer k, the last one is the input
+fn fraction_layers<F: PrimeField>(p: &[F], q: &[F]) -> Vec<(Vec<F>, Vec<F>)> {
+    let mut layers = vec![(p.to_vec(), q.to_vec())];
+
+    while layers[0].0.len() > 1 {
+        let (p, q) = &layers[0];
+        let half = p.len() / 2;
+
+        let next_p = (0..half)
+            .map(|i| p[i] * q[i + half] + p[i + half] * q[i])
+            .collect();
+        let next_q = (0..half).map(|i| q[i] * q[i + half]).collect();
+
+        layers.insert(0, (next_p, next_q));
+    }
+
+    layers
+}
+
+// absorbs the halves and moves both claims to layer k + 1 at (tau, point)
+fn reduce_to_line<F: PrimeField>(
+    point: &[F],
+    [p_0, p_1, q_0, q_1]: [F; 4],
+    transcript: &mut Transcript<F>,
+) -> (Vec<F>, F, F) {
+    transcript.append(&fq_vec_to_bytes(&[p_0, p_1, q_0, q_1]));
+    let tau = transcript.get_random_challenge();
+
+    (
+        [&[tau], point].concat(),
+        p_0 + tau * (p_1 - p_0),
+        q_0 + tau * (q_1 - q_0),
+    )
+}
+
+// the returned claims are on p and q at one point
+pub fn prove_fractional_sum<F: PrimeField>(
+    p: &MultilinearPoly<F>,
+    q: &MultilinearPoly<F>,
+    transcript: &mut Transcript<F>,
+) -> (
+    FractionalSumProof<F>,
+    EvaluationClaim<F>,
+    EvaluationClaim<F>,
+) {
+    if p.n_vars != q.n_vars {
+        panic!("numerators and denominators must have the same number of variables");
+    }
+
+    let n_vars = p.n_vars;
+    let layers = fraction_layers(&p.evals, &q.evals);
+    let root = (layers[0].0[0], layers[0].1[0]);
+
+    transcript.append(&fq_vec_to_bytes(&[root.0, root.1]));
+
+    let mut layer_proofs = Vec::with_capacity(n_vars.saturating_sub(1));
+    let mut layer_evaluations = Vec::with_capacity(n_vars);
+    let mut point = vec![];
+    let (mut p_claim, mut q_claim) = root;
+
+    for k in 0..n_vars {
+        let (next_p, next_q) = &layers[k + 1];
+        let (p_0, p_1) = next_p.split_at(1 << k);
+        let (q_0, q_1) = next_q.split_at(1 << k);
+
+        let evaluations = if k == 0 {
+            [p_0[0], p_1[0], q_0[0], q_1[0]]
+        } else {
+            let lambda = transcript.get_random_challenge();
+
+            let mut poly = VirtualPoly::new(k);
+            let eq = poly.add_table(MultilinearPoly::eq(&point));
+            let tables = [p_0, p_1, q_0, q_1]
+                .map(|table| poly.add_table(MultilinearPoly::new(table.to_vec())));
+            let [p_0, p_1, q_0, q_1] = tables;
+            poly.add_term(F::one(), vec![eq, p_0, q_1]);
+            poly.add_term(F::one(), vec![eq, p_1, q_0]);
+            poly.add_term(lambda, vec![eq, q_0, q_1]);
+
+            let (proof, challenges, folded) =
+                SumcheckProver::prove_rounds(&poly, p_claim + lambda * q_claim, transcript);
+
+            layer_proofs.push(proof.compress());
+            point = challenges;
+
+            tables.map(|table| folded.tables[table].evals[0])
+        };
+
+        layer_evaluations.push(evaluations);
+        (point, p_claim, q_claim) = reduce_to_line(&point, evaluations, transcript);
+    }
+
+    let proof = FractionalSumProof {
+        root,
+        layer_proofs,
+        layer_evaluations,
+    };
+
+    (
+        proof,
+        EvaluationClaim {
+            point: point.clone(),
+            value: p_claim,
+        },
+        EvaluationClaim {
+            point,
+            value: q_claim,
+        },
+    )
+}
+
+pub fn verify_fractional_sum<F: PrimeField>(
+    proof: &FractionalSumProof<F>,
+    n_vars: usize,
+    transcript: &mut Transcript<F>,
+) -> Result<(EvaluationClaim<F>, EvaluationClaim<F>), FractionalSumError> {
+    for (expected, got) in [
+        (n_vars.saturating_sub(1), proof.layer_proofs.len()),
+        (n_vars, proof.layer_evaluations.len()),
+    ] {
+        if expected != got {
+            return Err(FractionalSumError::WrongNumberOfLayers { expected, got });
+        }
+    }
+
+    transcript.append(&fq_vec_to_bytes(&[proof.root.0, proof.root.1]));
+
+    let mut point = vec![];
+    let (mut p_claim, mut q_claim) = proof.root;
+
+    for (k, evaluations) in proof.layer_evaluations.iter().enumerate() {
+        let [p_0, p_1, q_0, q_1] = *evaluations;
+
+        // the root has no variables to sum over, the claims are the fraction itself
+        let consistent = if k == 0 {
+            p_0 * q_1 + p_1 * q_0 == p_claim && q_0 * q_1 == q_claim
+        } else {
+            let lambda = transcript.get_random_challenge();
+
+            let layer_proof = &proof.layer_proofs[k - 1];
+            if layer_proof.claimed_sum != p_claim + lambda * q_claim {
+                return Err(FractionalSumError::ClaimMismatch { layer: k });
+            }
+
+            let sumcheck_claim = SumcheckVerifier::verify_compressed(layer_proof, k, 3, transcript)
+                .map_err(|error| FractionalSumError::Sumcheck { layer: k, error })?;
+
+            let eq = eq_eval(&point, &sumcheck_claim.point);
+            point = sumcheck_claim.point;
+
+            eq * (p_0 * q_1 + p_1 * q_0 + lambda * q_0 * q_1) == sumcheck_claim.value
+        };
+
+        if !consistent {
+            return Err(FractionalSumError::LayerMismatch { layer: k });
+        }
+
+        (point, p_claim, q_claim) = reduce_to_line(&point, *evaluations, transcript);
+    }
+
+    Ok((
+        EvaluationClaim {
+            point: point.clone(),
+            value: p_claim,
+        },
+        EvaluationClaim {
+            point,
+            value: q_claim,
+        },
+    ))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogUpProof<F: PrimeField> {
+    pub witness: FractionalSumProof<F>,
+    pub table: FractionalSumProof<F>,
+}
+
+// what is left to check against the commitments to witness, table and multiplicities
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogUpClaims<F: PrimeField> {
+    pub witness: EvaluationClaim<F>,
+    pub table: EvaluationClaim<F>,
+    pub multiplicities: EvaluationClaim<F>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogUpError {
+    Witness(FractionalSumError),
+    Table(FractionalSumError),
+    // the witness side has 1 in every numerator
+    WitnessNumerator,
+    ZeroDenominator,
+    SumMismatch,
+}
+
+impl fmt::Display for LogUpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogUpError::Witness(error) => write!(f, "witness sum: {}", error),
+            LogUpError::Table(error) => write!(f, "table sum: {}", error),
+            LogUpError::WitnessNumerator => write!(f, "witness numerators are not all one"),
+            LogUpError::ZeroDenominator => write!(f, "a sum has a zero denominator"),
+            LogUpError::SumMismatch => write!(f, "witness and table sums differ"),
+        }
+    }
+}
+
+impl std::error::Error for LogUpError {}
+
+// how often every table entry appears in the witness. panics on a witness entry that is not
+// in the table, there is no honest proof for it
+pub fn multiplicities<F: PrimeField>(
+    witness: &MultilinearPoly<F>,
+    table: &MultilinearPoly<F>,
+) -> MultilinearPoly<F> {
+    let positions: HashMap<F, usize> = table
+        .evals
+        .iter()
+        .enumerate()
+        .map(|(j, entry)| (*entry, j))
+        .collect();
+
+    let mut counts = vec![F::zero(); table.evals.len()];
+    for value in witness.evals.iter() {
+        match positions.get(value) {
+            Some(j) => counts[*j] += F::one(),
+            None => panic!("witness value is not in the table"),
+        }
+    }
+
+    MultilinearPoly::new(counts)
+}
+
+fn denominators<F: PrimeField>(gamma: F, values: &MultilinearPoly<F>) -> MultilinearPoly<F> {
+    MultilinearPoly::new(values.evals.iter().map(|value| gamma - value).collect())
+}
+
+// the transcript should already bind witness, table and multiplicities
+pub fn prove<F: PrimeField>(
+    witness: &MultilinearPoly<F>,
+    table: &MultilinearPoly<F>,
+    multiplicities: &MultilinearPoly<F>,
+    transcript: &mut Transcript<F>,
+) -> (LogUpProof<F>, LogUpClaims<F>) {
+    let gamma = transcript.get_random_challenge();
+
+    let ones = MultilinearPoly::new(vec![F::one(); witness.evals.len()]);
+    let (witness_proof, _, witness_q) =
+        prove_fractional_sum(&ones, &denominators(gamma, witness), transcript);
+    let (table_proof, table_p, table_q) =
+        prove_fractional_sum(multiplicities, &denominators(gamma, table), transcript);
+
+    let proof = LogUpProof {
+        witness: witness_proof,
+        table: table_proof,
+    };
+
+    let claims = LogUpClaims {
+        witness: EvaluationClaim {
+            point: witness_q.point,
+            value: gamma - witness_q.value,
+        },
+        table: EvaluationClaim {
+            point: table_q.point,
+            value: gamma - table_q.value,
+        },
+        multiplicities: table_p,
+    };
+
+    (proof, claims)
+}
+
+pub fn verify<F: PrimeField>(
+    proof: &LogUpProof<F>,
+    witness_vars: usize,
+    table_vars: usize,
+    transcript: &mut Transcript<F>,
+) -> Result<LogUpClaims<F>, LogUpError> {
+    let gamma = transcript.get_random_challenge();
+
+    let (witness_p, witness_q) = verify_fractional_sum(&proof.witness, witness_vars, transcript)
+        .map_err(LogUpError::Witness)?;
+    let (table_p, table_q) =
+        verify_fractional_sum(&proof.table, table_vars, transcript).map_err(LogUpError::Table)?;
+
+    // the extension of an all ones table is 1 everywhere
+    if witness_p.value != F::one() {
+        return Err(LogUpError::WitnessNumerator);
+    }
+
+    let (witness_sum, table_sum) = (proof.witness.root, proof.table.root);
+    if witness_sum.1.is_zero() || table_sum.1.is_zero() {
+        return Err(LogUpError::ZeroDenominator);
+    }
+
+    if witness_sum.0 * table_sum.1 != table_sum.0 * witness_sum.1 {
+        return Err(LogUpError::SumMismatch);
+    }
+
+    Ok(LogUpClaims {
+        witness: EvaluationClaim {
+            point: witness_q.point,
+            value: gamma - witness_q.value,
+        },
+        table: EvaluationClaim {
+            point: table_q.point,
+            value: gamma - table_q.value,
+        },
+        multiplicities: table_p,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        multiplicities, prove, prove_fractional_sum, verify, verify_fractional_sum, LogUpError,
+    };
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::tests::{committed_transcript, to_field};
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use ark_bn254::Fr;
+    use ark_ff::Field;
+
+    const COMMITMENTS: &[u8] = b"commitments to witness, table and multiplicities";
+
+    // a 3 bit range check
+    fn range_table() -> MultilinearPoly<Fr> {
+        MultilinearPoly::new(to_field((0..8).collect()))
+    }
+
+    #[test]
+    fn fractional_sum_adds_up_the_fractions() {
+        let p = MultilinearPoly::new(to_field(vec![1, 2, 3, 4]));
+        let q = MultilinearPoly::new(to_field(vec![5, 6, 7, 8]));
+
+        let (proof, p_claim, q_claim) = prove_fractional_sum(&p, &q, &mut Transcript::new());
+
+        let expected: Fr = p
+            .evals
+            .iter()
+            .zip(q.evals.iter())
+            .map(|(p, q)| *p * q.inverse().unwrap())
+            .sum();
+        assert_eq!(proof.root.0 * proof.root.1.inverse().unwrap(), expected);
+
+        let claims = verify_fractional_sum(&proof, 2, &mut Transcript::new()).unwrap();
+
+        assert_eq!(claims, (p_claim, q_claim));
+        assert!(claims.0.check(|point| p.evaluate(point.to_vec())));
+        assert!(claims.1.check(|point| q.evaluate(point.to_vec())));
+    }
+
+    #[test]
+    fn it_proves_a_range_check() {
+        let witness = MultilinearPoly::new(to_field(vec![3, 7, 0, 3, 3, 5, 1, 7]));
+        let table = range_table();
+        let multiplicities = multiplicities(&witness, &table);
+
+        assert_eq!(multiplicities.evals, to_field(vec![1, 1, 0, 3, 0, 1, 0, 2]));
+
+        let (proof, prover_claims) = prove(
+            &witness,
+            &table,
+            &multiplicities,
+            &mut committed_transcript(COMMITMENTS),
+        );
+        let claims = verify(&proof, 3, 3, &mut committed_transcript(COMMITMENTS)).unwrap();
+
+        assert_eq!(claims, prover_claims);
+        assert!(claims
+            .witness
+            .check(|point| witness.evaluate(point.to_vec())));
+        assert!(claims.table.check(|point| table.evaluate(point.to_vec())));
+        assert!(claims
+            .multiplicities
+            .check(|point| multiplicities.evaluate(point.to_vec())));
+    }
+
+    #[test]
+    fn it_handles_tables_of_another_size() {
+        let witness = MultilinearPoly::new(to_field(vec![6, 2]));
+        let table = range_table();
+        let multiplicities = multiplicities(&witness, &table);
+
+        let (proof, _) = prove(
+            &witness,
+            &table,
+            &multiplicities,
+            &mut committed_transcript(COMMITMENTS),
+        );
+        let claims = verify(&proof, 1, 3, &mut committed_transcript(COMMITMENTS)).unwrap();
+
+        assert!(claims
+            .witness
+            .check(|point| witness.evaluate(point.to_vec())));
+    }
+
+    #[test]
+    fn it_rejects_a_value_outside_the_table() {
+        let witness = MultilinearPoly::new(to_field(vec![3, 7, 0, 3, 3, 5, 1, 9]));
+        let table = range_table();
+        // what 9 would need, a 7 that was never looked up
+        let multiplicities = MultilinearPoly::new(to_field(vec![1, 1, 0, 3, 0, 1, 0, 2]));
+
+        let (proof, _) = prove(
+            &witness,
+            &table,
+            &multiplicities,
+            &mut committed_transcript(COMMITMENTS),
+        );
+        assert_eq!(
+            verify(&proof, 3, 3, &mut committed_transcript(COMMITMENTS)),
+            Err(LogUpError::SumMismatch)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "witness value is not in the table")]
+    fn multiplicities_need_every_value_in_the_table() {
+        multiplicities(&MultilinearPoly::new(to_field(vec![8, 1])), &range_table());
+    }
+}
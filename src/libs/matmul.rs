@@ -0,0 +1,340 @@
+use super::fiat_shamir::{fq_vec_to_bytes, Transcript};
+use super::multilinear_poly::{MultilinearPoly, ProductPoly, SumPoly};
+use super::sum_check_protocol::{
+    CompressedSumcheckProof, EvaluationClaim, SumcheckError, SumcheckProver, SumcheckVerifier,
+};
+use ark_ff::PrimeField;
+use std::fmt;
+
+// log2 of the dimensions of C = A * B, with A 2^row_vars x 2^inner_vars and B
+// 2^inner_vars x 2^col_vars. matrices are row major tables, row index in the high variables
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatMulShape {
+    pub row_vars: usize,
+    pub inner_vars: usize,
+    pub col_vars: usize,
+}
+
+// Thaler's protocol: for random r_i and r_k, C(r_i, r_k) = sum_j A(r_i, j) * B(j, r_k), so
+// one degree 2 sumcheck over the inner index reduces the product to A and B at a point each
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatMulProof<F: PrimeField> {
+    pub sumcheck: CompressedSumcheckProof<F>,
+    // A(r_i, r_j), B(r_j, r_k)
+    pub evaluations: (F, F),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatMulError {
+    WrongShape { expected: usize, got: usize },
+    ClaimMismatch,
+    Sumcheck(SumcheckError),
+    EvaluationMismatch,
+}
+
+impl fmt::Display for MatMulError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatMulError::WrongShape { expected, got } => {
+                write!(f, "expected C over {} variables, got {}", expected, got)
+            }
+            MatMulError::ClaimMismatch => write!(f, "sumcheck does not start from C"),
+            MatMulError::Sumcheck(error) => write!(f, "{}", error),
+            MatMulError::EvaluationMismatch => {
+                write!(f, "A and B evaluations do not match the sumcheck")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MatMulError {}
+
+impl MatMulShape {
+    fn check(&self, a: &MultilinearPoly<impl PrimeField>, b: &MultilinearPoly<impl PrimeField>) {
+        if a.n_vars != self.row_vars + self.inner_vars
+            || b.n_vars != self.inner_vars + self.col_vars
+        {
+            panic!("matrices do not match the shape");
+        }
+    }
+}
+
+pub fn multiply<F: PrimeField>(
+    a: &MultilinearPoly<F>,
+    b: &MultilinearPoly<F>,
+    shape: MatMulShape,
+) -> MultilinearPoly<F> {
+    shape.check(a, b);
+
+    let (inner, cols) = (1 << shape.inner_vars, 1 << shape.col_vars);
+    let mut c = vec![F::zero(); 1 << (shape.row_vars + shape.col_vars)];
+
+    for (i, row) in a.evals.chunks(inner).enumerate() {
+        for (j, a_ij) in row.iter().enumerate() {
+            for (k, b_jk) in b.evals[j * cols..(j + 1) * cols].iter().enumerate() {
+                c[i * cols + k] += *a_ij * b_jk;
+            }
+        }
+    }
+
+    MultilinearPoly::new(c)
+}
+
+// A(r_i, j) for every j. the eq table weights whole rows, so this is one pass over A
+// instead of a fold per variable
+fn restrict_rows<F: PrimeField>(a: &MultilinearPoly<F>, r_i: &[F], inner_vars: usize) -> Vec<F> {
+    let eq = MultilinearPoly::eq(r_i);
+    let mut row = vec![F::zero(); 1 << inner_vars];
+
+    for (weight, a_row) in eq.evals.iter().zip(a.evals.chunks(row.len())) {
+        for (acc, value) in row.iter_mut().zip(a_row) {
+            *acc += *weight * value;
+        }
+    }
+
+    row
+}
+
+// B(j, r_k) for every j, one inner product with the eq table per row of B
+fn restrict_cols<F: PrimeField>(b: &MultilinearPoly<F>, r_k: &[F]) -> Vec<F> {
+    let eq = MultilinearPoly::eq(r_k);
+
+    b.evals
+        .chunks(eq.evals.len())
+        .map(|b_row| b_row.iter().zip(eq.evals.iter()).map(|(b, e)| *b * e).sum())
+        .collect()
+}
+
+// absorbs C and draws r_i and r_k
+fn output_point<F: PrimeField>(
+    c: &MultilinearPoly<F>,
+    shape: MatMulShape,
+    transcript: &mut Transcript<F>,
+) -> (Vec<F>, Vec<F>) {
+    transcript.append(&fq_vec_to_bytes(&c.evals));
+
+    let mut challenges =
+        |n: usize| -> Vec<F> { (0..n).map(|_| transcript.get_random_challenge()).collect() };
+
+    (challenges(shape.row_vars), challenges(shape.col_vars))
+}
+
+// the prover work is the two restrictions, O(mn + np), plus a sumcheck over 2^inner_vars
+// values. the returned claims are on A and B, which the caller still has to open
+pub fn prove<F: PrimeField>(
+    a: &MultilinearPoly<F>,
+    b: &MultilinearPoly<F>,
+    c: &MultilinearPoly<F>,
+    shape: MatMulShape,
+    transcript: &mut Transcript<F>,
+) -> (MatMulProof<F>, (EvaluationClaim<F>, EvaluationClaim<F>)) {
+    shape.check(a, b);
+
+    let (r_i, r_k) = output_point(c, shape, transcript);
+    let claimed_sum = c.evaluate([r_i.as_slice(), &r_k].concat());
+
+    let poly = SumPoly::new(vec![ProductPoly::new(vec![
+        restrict_rows(a, &r_i, shape.inner_vars),
+        restrict_cols(b, &r_k),
+    ])]);
+
+    let (sumcheck, r_j, folded) = SumcheckProver::prove_rounds(&poly, claimed_sum, transcript);
+    let [a_eval, b_eval] = [0, 1].map(|index| folded.polys[0].evaluation[index].evals[0]);
+
+    transcript.append(&fq_vec_to_bytes(&[a_eval, b_eval]));
+
+    let proof = MatMulProof {
+        sumcheck: sumcheck.compress(),
+        evaluations: (a_eval, b_eval),
+    };
+
+    let claims = (
+        EvaluationClaim {
+            point: [r_i, r_j.clone()].concat(),
+            value: a_eval,
+        },
+        EvaluationClaim {
+            point: [r_j, r_k].concat(),
+            value: b_eval,
+        },
+    );
+
+    (proof, claims)
+}
+
+pub fn verify<F: PrimeField>(
+    c: &MultilinearPoly<F>,
+    shape: MatMulShape,
+    proof: &MatMulProof<F>,
+    transcript: &mut Transcript<F>,
+) -> Result<(EvaluationClaim<F>, EvaluationClaim<F>), MatMulError> {
+    if c.n_vars != shape.row_vars + shape.col_vars {
+        return Err(MatMulError::WrongShape {
+            expected: shape.row_vars + shape.col_vars,
+            got: c.n_vars,
+        });
+    }
+
+    let (r_i, r_k) = output_point(c, shape, transcript);
+
+    if proof.sumcheck.claimed_sum != c.evaluate([r_i.as_slice(), &r_k].concat()) {
+        return Err(MatMulError::ClaimMismatch);
+    }
+
+    let claim =
+        SumcheckVerifier::verify_compressed(&proof.sumcheck, shape.inner_vars, 2, transcript)
+            .map_err(MatMulError::Sumcheck)?;
+
+    let (a_eval, b_eval) = proof.evaluations;
+    if a_eval * b_eval != claim.value {
+        return Err(MatMulError::EvaluationMismatch);
+    }
+
+    transcript.append(&fq_vec_to_bytes(&[a_eval, b_eval]));
+
+    Ok((
+        EvaluationClaim {
+            point: [r_i, claim.point.clone()].concat(),
+            value: a_eval,
+        },
+        EvaluationClaim {
+            point: [claim.point, r_k].concat(),
+            value: b_eval,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{multiply, prove, restrict_cols, restrict_rows, verify, MatMulError, MatMulShape};
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use ark_bn254::Fr;
+
+    // 2 x 4 times 4 x 2
+    const SHAPE: MatMulShape = MatMulShape {
+        row_vars: 1,
+        inner_vars: 2,
+        col_vars: 1,
+    };
+
+    fn matrices() -> (MultilinearPoly<Fr>, MultilinearPoly<Fr>) {
+        (
+            MultilinearPoly::new(to_field(vec![1, 2, 3, 4, 5, 6, 7, 8])),
+            MultilinearPoly::new(to_field(vec![1, 0, 0, 1, 2, 1, 1, 3])),
+        )
+    }
+
+    #[test]
+    fn it_multiplies_row_major_tables() {
+        let (a, b) = matrices();
+
+        // [1 2 3 4] * columns [1 0 2 1] and [0 1 1 3]
+        assert_eq!(
+            multiply(&a, &b, SHAPE).evals,
+            to_field(vec![11, 17, 27, 37])
+        );
+    }
+
+    #[test]
+    fn it_restricts_to_the_output_point() {
+        let (a, b) = matrices();
+        let (r_i, r_k) = (to_field(vec![5]), to_field(vec![7]));
+
+        let row = restrict_rows(&a, &r_i, SHAPE.inner_vars);
+        let col = restrict_cols(&b, &r_k);
+
+        for j in 0..4u64 {
+            let bits = to_field(vec![j >> 1, j & 1]);
+
+            assert_eq!(
+                row[j as usize],
+                a.evaluate([r_i.clone(), bits.clone()].concat())
+            );
+            assert_eq!(col[j as usize], b.evaluate([bits, r_k.clone()].concat()));
+        }
+    }
+
+    #[test]
+    fn it_proves_the_product() {
+        let (a, b) = matrices();
+        let c = multiply(&a, &b, SHAPE);
+
+        let (proof, prover_claims) = prove(&a, &b, &c, SHAPE, &mut Transcript::new());
+        let (a_claim, b_claim) = verify(&c, SHAPE, &proof, &mut Transcript::new()).unwrap();
+
+        assert_eq!((a_claim.clone(), b_claim.clone()), prover_claims);
+        assert!(a_claim.check(|point| a.evaluate(point.to_vec())));
+        assert!(b_claim.check(|point| b.evaluate(point.to_vec())));
+    }
+
+    #[test]
+    fn it_handles_vectors_and_scalars() {
+        // row vector times matrix, then a dot product
+        let b = MultilinearPoly::new(to_field(vec![2, 7, 1, 8, 2, 8, 1, 8]));
+
+        for (a, shape) in [
+            (
+                to_field(vec![1, 2, 3, 4]),
+                MatMulShape {
+                    row_vars: 0,
+                    inner_vars: 2,
+                    col_vars: 1,
+                },
+            ),
+            (
+                to_field(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+                MatMulShape {
+                    row_vars: 0,
+                    inner_vars: 3,
+                    col_vars: 0,
+                },
+            ),
+        ] {
+            let a = MultilinearPoly::new(a);
+            let c = multiply(&a, &b, shape);
+
+            let (proof, _) = prove(&a, &b, &c, shape, &mut Transcript::new());
+            let (a_claim, b_claim) = verify(&c, shape, &proof, &mut Transcript::new()).unwrap();
+
+            assert!(a_claim.check(|point| a.evaluate(point.to_vec())));
+            assert!(b_claim.check(|point| b.evaluate(point.to_vec())));
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_product() {
+        let (a, b) = matrices();
+        let c = multiply(&a, &b, SHAPE);
+        let (proof, _) = prove(&a, &b, &c, SHAPE, &mut Transcript::new());
+
+        let wrong_c = MultilinearPoly::new(to_field(vec![11, 17, 27, 38]));
+        assert_eq!(
+            verify(&wrong_c, SHAPE, &proof, &mut Transcript::new()),
+            Err(MatMulError::ClaimMismatch)
+        );
+
+        let mut wrong_evaluation = proof.clone();
+        wrong_evaluation.evaluations.1 += Fr::from(1);
+        assert_eq!(
+            verify(&c, SHAPE, &wrong_evaluation, &mut Transcript::new()),
+            Err(MatMulError::EvaluationMismatch)
+        );
+
+        // a consistent proof for other A only fails at the opening
+        let other_a = MultilinearPoly::new(to_field(vec![1, 2, 3, 4, 5, 6, 8, 7]));
+        let other_c = multiply(&other_a, &b, SHAPE);
+        let (other_proof, _) = prove(&other_a, &b, &other_c, SHAPE, &mut Transcript::new());
+        let (a_claim, _) = verify(&other_c, SHAPE, &other_proof, &mut Transcript::new()).unwrap();
+        assert!(!a_claim.check(|point| a.evaluate(point.to_vec())));
+
+        assert_eq!(
+            verify(&a, SHAPE, &proof, &mut Transcript::new()),
+            Err(MatMulError::WrongShape {
+                expected: 2,
+                got: 3
+            })
+        );
+    }
+}
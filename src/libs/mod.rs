@@ -1,8 +1,25 @@
+pub mod batched_sum_check;
+pub mod domain_sum_check;
+pub mod extension_sum_check;
 pub mod fiat_shamir;
 pub mod gkr_circuits_impl;
 pub mod gkr_protocol;
+pub mod goldilocks;
+pub mod grand_product;
+pub mod logup;
+pub mod matmul;
 pub mod multilinear_poly;
+pub mod pedersen;
+pub mod permutation_check;
 pub mod polynomial;
+pub mod r1cs;
 pub mod shamir_secret_sharing;
+#[cfg(test)]
+mod soundness_tests;
+pub mod sparse_multilinear_poly;
+pub mod spartan;
+pub mod streaming_sum_check;
 pub mod sum_check_protocol;
 pub mod transcript;
+pub mod virtual_poly;
+pub mod zk_sum_check;
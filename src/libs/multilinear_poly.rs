@@ -1,14 +1,28 @@
-use ark_ff::PrimeField;
+use ark_ff::{Field, PrimeField};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fmt;
 use std::ops::{Add, Mul, Sub};
 
+// which end of a hypercube index holds variable 0.
+// MultilinearPoly always stores MsbFirst: evals[0b011] is f(0, 1, 1), pairs/insert_bit
+// address variable `index` at bit n_vars - index - 1, and evaluate fixes variable 0 first
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VariableOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
 #[derive(Clone, Debug, PartialEq)]
-pub(crate) struct MultilinearPoly<F: PrimeField> {
+pub struct MultilinearPoly<F: Field> {
     pub(crate) evals: Vec<F>,
     pub(crate) n_vars: usize,
 }
 
-impl<F: PrimeField> MultilinearPoly<F> {
-    pub(crate) fn new(evaluations: Vec<F>) -> Self {
+// any field, so a table over an extension is a MultilinearPoly too. whatever needs the
+// prime field structure, like lifting into an extension, is in the impl below
+impl<F: Field> MultilinearPoly<F> {
+    pub fn new(evaluations: Vec<F>) -> Self {
         let n_vars: usize = evaluations.len().ilog2() as usize;
         if evaluations.len() != 1 << n_vars {
             panic!("what are you doing?");
@@ -47,7 +61,7 @@ impl<F: PrimeField> MultilinearPoly<F> {
         result.evals[0]
     }
 
-    pub(crate) fn partial_evaluate(&self, index: usize, value: &F) -> Self {
+    pub fn partial_evaluate(&self, index: usize, value: &F) -> Self {
         // use index to generate pairing
         // linear interpolate and evaluate <-- easy
 
@@ -56,19 +70,36 @@ impl<F: PrimeField> MultilinearPoly<F> {
         // 10 - (010, 110) - (2, 6)
         // 11 - (011, 111) - (3, 7)
 
-        let mut result = vec![];
         // what does this need?
         // index <- 0 -> a
         // len of hypercube
-        for (a, b) in pairs(index, self.n_vars).into_iter() {
+        let interpolate = |(a, b): (usize, usize)| {
             let a = self.evals[a];
             let b = self.evals[b];
-            result.push(a + *value * (b - a));
-        }
+            a + *value * (b - a)
+        };
+
+        #[cfg(feature = "parallel")]
+        let result = pairs(index, self.n_vars)
+            .into_par_iter()
+            .map(interpolate)
+            .collect();
+
+        #[cfg(not(feature = "parallel"))]
+        let result = pairs(index, self.n_vars)
+            .into_iter()
+            .map(interpolate)
+            .collect();
 
         Self::new(result)
     }
 
+    // partial_evaluate(0, value) without allocating: pairs for variable 0 are (i, i + half)
+    pub(crate) fn fold_in_place(&mut self, value: &F) {
+        fold_evals_in_place(&mut self.evals, value);
+        self.n_vars -= 1;
+    }
+
     pub fn multi_partial_evaluate(&self, values: &[F]) -> Self {
         if values.len() > self.n_vars {
             panic!("Invalid number of values");
@@ -76,7 +107,7 @@ impl<F: PrimeField> MultilinearPoly<F> {
 
         let mut poly = self.clone();
 
-        for (i, value) in values.iter().enumerate() {
+        for value in values.iter() {
             poly = poly.partial_evaluate(0, value);
         }
 
@@ -88,6 +119,231 @@ impl<F: PrimeField> MultilinearPoly<F> {
 
         Self::new(result)
     }
+
+    // evaluations laid out in `order`, e.g. from a library that puts variable 0 in the low bit
+    pub fn from_evals_with_order(evaluations: Vec<F>, order: VariableOrder) -> Self {
+        let poly = Self::new(evaluations);
+
+        match order {
+            VariableOrder::MsbFirst => poly,
+            VariableOrder::LsbFirst => poly.reverse_variables(),
+        }
+    }
+
+    pub fn evals_with_order(&self, order: VariableOrder) -> Vec<F> {
+        match order {
+            VariableOrder::MsbFirst => self.evals.clone(),
+            VariableOrder::LsbFirst => self.reverse_variables().evals,
+        }
+    }
+
+    // point[i] is the value of variable i
+    pub fn index_of(&self, point: &[bool]) -> usize {
+        if point.len() != self.n_vars {
+            panic!("Invalid number of values");
+        }
+
+        point
+            .iter()
+            .fold(0, |index, bit| (index << 1) | *bit as usize)
+    }
+
+    pub fn get(&self, point: &[bool]) -> F {
+        self.evals[self.index_of(point)]
+    }
+
+    // variable i of the result is variable permutation[i] of self
+    pub fn permute_variables(&self, permutation: &[usize]) -> Self {
+        if permutation.len() != self.n_vars {
+            panic!("not a permutation of the variables");
+        }
+
+        let mut seen = vec![false; self.n_vars];
+        for var in permutation {
+            if *var >= self.n_vars || seen[*var] {
+                panic!("not a permutation of the variables");
+            }
+            seen[*var] = true;
+        }
+
+        let result = (0..self.evals.len())
+            .map(|index| {
+                let old_index =
+                    permutation
+                        .iter()
+                        .enumerate()
+                        .fold(0, |acc, (new_var, old_var)| {
+                            let bit = (index >> (self.n_vars - new_var - 1)) & 1;
+                            acc | (bit << (self.n_vars - old_var - 1))
+                        });
+
+                self.evals[old_index]
+            })
+            .collect();
+
+        Self::new(result)
+    }
+
+    // switches between MsbFirst and LsbFirst layouts
+    pub fn reverse_variables(&self) -> Self {
+        let permutation: Vec<usize> = (0..self.n_vars).rev().collect();
+
+        self.permute_variables(&permutation)
+    }
+
+    // monomial coefficients, indexed like evals: coefficients[0b011] belongs to x_1 * x_2.
+    // moebius transform, O(n * 2^n)
+    pub fn to_coefficients(&self) -> Vec<F> {
+        let mut coefficients = self.evals.clone();
+
+        for bit in 0..self.n_vars {
+            for index in 0..coefficients.len() {
+                if index & (1 << bit) != 0 {
+                    let low = coefficients[index ^ (1 << bit)];
+                    coefficients[index] -= low;
+                }
+            }
+        }
+
+        coefficients
+    }
+
+    // eq(point, x) = prod point_i * x_i + (1 - point_i) * (1 - x_i) over the hypercube, so
+    // that sum eq(point, x) * f(x) = f(point) for any multilinear f
+    pub fn eq(point: &[F]) -> Self {
+        let mut evals = vec![F::one()];
+
+        for r in point {
+            evals = evals
+                .iter()
+                .flat_map(|eval| [*eval * (F::one() - r), *eval * r])
+                .collect();
+        }
+
+        Self::new(evals)
+    }
+
+    // zeta transform, inverse of to_coefficients
+    pub fn from_coefficients(coefficients: Vec<F>) -> Self {
+        let mut evals = coefficients;
+        let n_vars = evals.len().ilog2() as usize;
+
+        for bit in 0..n_vars {
+            for index in 0..evals.len() {
+                if index & (1 << bit) != 0 {
+                    let low = evals[index ^ (1 << bit)];
+                    evals[index] += low;
+                }
+            }
+        }
+
+        Self::new(evals)
+    }
+
+    // 2ab + 3bc over (a, b, c) is from_monomials(3, &[(2, vec![0, 1]), (3, vec![1, 2])])
+    pub fn from_monomials(n_vars: usize, monomials: &[(F, Vec<usize>)]) -> Self {
+        let mut coefficients = vec![F::zero(); 1 << n_vars];
+
+        for (coefficient, vars) in monomials {
+            let index = vars.iter().fold(0, |index, var| {
+                if *var >= n_vars {
+                    panic!("Invalid variable index");
+                }
+                index | (1 << (n_vars - var - 1))
+            });
+
+            coefficients[index] += coefficient;
+        }
+
+        Self::from_coefficients(coefficients)
+    }
+}
+
+// prints the coefficient form, e.g. "2ab + 3bc". more than 26 variables are printed as x_i
+impl<F: PrimeField> MultilinearPoly<F> {
+    // fold_into, then the remaining variables in the extension
+    pub fn evaluate_in<E: Field<BasePrimeField = F>>(&self, values: &[E]) -> E {
+        if values.len() != self.n_vars {
+            panic!("Invalid number of values");
+        }
+
+        if values.is_empty() {
+            return E::from_base_prime_field(self.evals[0]);
+        }
+
+        let mut poly = self.fold_into(&values[0]);
+        for value in values[1..].iter() {
+            poly.fold_in_place(value);
+        }
+
+        poly.evals[0]
+    }
+
+    // the first fold of a small field table at an extension challenge. the products are
+    // base times extension, and the table only exists in the extension after it
+    pub fn fold_into<E: Field<BasePrimeField = F>>(&self, value: &E) -> MultilinearPoly<E> {
+        let half = self.evals.len() / 2;
+
+        MultilinearPoly::new(
+            (0..half)
+                .map(|i| {
+                    let low = self.evals[i];
+                    E::from_base_prime_field(low)
+                        + value.mul_by_base_prime_field(&(self.evals[i + half] - low))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<F: PrimeField> fmt::Display for MultilinearPoly<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut wrote_term = false;
+
+        for (index, coefficient) in self.to_coefficients().iter().enumerate() {
+            if coefficient.is_zero() {
+                continue;
+            }
+
+            // print p - k as -k
+            let negated = -*coefficient;
+            let (sign, magnitude) = if negated.into_bigint() < coefficient.into_bigint() {
+                ("-", negated)
+            } else {
+                ("+", *coefficient)
+            };
+
+            match (wrote_term, sign) {
+                (false, "-") => write!(f, "-")?,
+                (true, _) => write!(f, " {} ", sign)?,
+                _ => {}
+            }
+
+            let monomial: String = (0..self.n_vars)
+                .filter(|var| index & (1 << (self.n_vars - var - 1)) != 0)
+                .map(|var| {
+                    if self.n_vars <= 26 {
+                        ((b'a' + var as u8) as char).to_string()
+                    } else {
+                        format!("x_{}", var)
+                    }
+                })
+                .collect();
+
+            if monomial.is_empty() || !magnitude.is_one() {
+                write!(f, "{}", magnitude)?;
+            }
+            write!(f, "{}", monomial)?;
+
+            wrote_term = true;
+        }
+
+        if !wrote_term {
+            write!(f, "0")?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<F: PrimeField> Add for MultilinearPoly<F> {
@@ -135,6 +391,101 @@ impl<F: PrimeField> Sub for MultilinearPoly<F> {
     }
 }
 
+// how the tail of a non power of two evaluation vector is filled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Padding<F: PrimeField> {
+    Zeros,
+    RepeatLast,
+    Value(F),
+}
+
+impl<F: PrimeField> Padding<F> {
+    fn fill_value(&self, evaluations: &[F]) -> F {
+        match self {
+            Padding::Zeros => F::zero(),
+            Padding::RepeatLast => *evaluations.last().unwrap(),
+            Padding::Value(value) => *value,
+        }
+    }
+}
+
+// keeps track of how many of the evaluations are real so sums and outputs
+// can leave the padded points out
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaddedMultilinearPoly<F: PrimeField> {
+    pub(crate) poly: MultilinearPoly<F>,
+    pub(crate) logical_len: usize,
+    pub(crate) padding: Padding<F>,
+}
+
+impl<F: PrimeField> PaddedMultilinearPoly<F> {
+    // pads to the next power of two
+    pub fn new(evaluations: Vec<F>, padding: Padding<F>) -> Self {
+        if evaluations.is_empty() {
+            panic!("cannot pad an empty evaluation vector");
+        }
+
+        let n_vars = evaluations.len().next_power_of_two().ilog2() as usize;
+
+        Self::with_n_vars(evaluations, padding, n_vars)
+    }
+
+    // pads to exactly 2^n_vars, e.g. a single output still needs one variable
+    pub fn with_n_vars(mut evaluations: Vec<F>, padding: Padding<F>, n_vars: usize) -> Self {
+        let logical_len = evaluations.len();
+
+        if logical_len == 0 || logical_len > 1 << n_vars {
+            panic!(
+                "{} evaluations do not fit in {} variables",
+                logical_len, n_vars
+            );
+        }
+
+        let fill = padding.fill_value(&evaluations);
+        evaluations.resize(1 << n_vars, fill);
+
+        Self {
+            poly: MultilinearPoly::new(evaluations),
+            logical_len,
+            padding,
+        }
+    }
+
+    pub fn poly(&self) -> &MultilinearPoly<F> {
+        &self.poly
+    }
+
+    pub fn into_poly(self) -> MultilinearPoly<F> {
+        self.poly
+    }
+
+    pub fn logical_len(&self) -> usize {
+        self.logical_len
+    }
+
+    pub fn padding(&self) -> Padding<F> {
+        self.padding
+    }
+
+    pub fn logical_evals(&self) -> &[F] {
+        &self.poly.evals[..self.logical_len]
+    }
+
+    // what the padded points add to a sum over the whole hypercube
+    pub fn padding_sum(&self) -> F {
+        self.poly.evals[self.logical_len..].iter().sum()
+    }
+
+    pub fn logical_sum(&self) -> F {
+        self.logical_evals().iter().sum()
+    }
+
+    // the sum a sumcheck over the padded poly has to claim
+    pub fn hypercube_sum(&self) -> F {
+        self.logical_sum() + self.padding_sum()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ProductPoly<F: PrimeField> {
     pub evaluation: Vec<MultilinearPoly<F>>,
@@ -183,7 +534,11 @@ impl<F: PrimeField> ProductPoly<F> {
     }
 
     fn reduce(&self) -> Vec<F> {
-        (self.evaluation[0].clone() * self.evaluation[1].clone()).evals
+        self.evaluation
+            .iter()
+            .skip(1)
+            .fold(self.evaluation[0].clone(), |acc, poly| acc * poly.clone())
+            .evals
     }
 
     fn get_degree(&self) -> usize {
@@ -193,9 +548,9 @@ impl<F: PrimeField> ProductPoly<F> {
 
 impl<F: PrimeField> SumPoly<F> {
     pub fn new(polys: Vec<ProductPoly<F>>) -> Self {
-        let degree_1 = polys[0].get_degree();
-        if polys.iter().any(|poly| poly.get_degree() != degree_1) {
-            panic!("all product polys must have same degree");
+        let n_vars = polys[0].evaluation[0].n_vars;
+        if polys.iter().any(|poly| poly.evaluation[0].n_vars != n_vars) {
+            panic!("all product polys must have same number of variables");
         }
 
         Self { polys }
@@ -219,20 +574,23 @@ impl<F: PrimeField> SumPoly<F> {
     }
 
     pub fn reduce(&self) -> Vec<F> {
-        let poly_a = &self.polys[0].reduce();
-        let poly_b = &self.polys[1].reduce();
-
-        let result = poly_a
+        self.polys
             .iter()
-            .zip(poly_b.iter())
-            .map(|(a, b)| *a + *b)
-            .collect();
-
-        result
+            .skip(1)
+            .fold(self.polys[0].reduce(), |acc, poly| {
+                acc.iter()
+                    .zip(poly.reduce().iter())
+                    .map(|(a, b)| *a + *b)
+                    .collect()
+            })
     }
 
     pub fn get_degree(&self) -> usize {
-        self.polys[0].get_degree()
+        self.polys
+            .iter()
+            .map(|poly| poly.get_degree())
+            .max()
+            .unwrap()
     }
 }
 
@@ -281,17 +639,80 @@ fn insert_bit(value: usize, index: usize) -> usize {
     let low = value & mask;
 
     // high | new_bit | low
-    high << index + 1 | low
+    (high << (index + 1)) | low
+}
+
+// eq(a, b) for two points, the eq table of a evaluated at b
+pub fn eq_eval<F: PrimeField>(a: &[F], b: &[F]) -> F {
+    if a.len() != b.len() {
+        panic!("points must have the same number of variables");
+    }
+
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| *x * y + (F::one() - x) * (F::one() - y))
+        .product()
+}
+
+// fold_in_place on bare evals, for tables over fields that are not prime fields
+pub(crate) fn fold_evals_in_place<F: Field>(evals: &mut Vec<F>, value: &F) {
+    let half = evals.len() / 2;
+    let (low, high) = evals.split_at_mut(half);
+    let fold = |(a, b): (&mut F, &F)| *a += *value * (*b - *a);
+
+    #[cfg(feature = "parallel")]
+    low.par_iter_mut().zip(high.par_iter()).for_each(fold);
+
+    #[cfg(not(feature = "parallel"))]
+    low.iter_mut().zip(high.iter()).for_each(fold);
+
+    evals.truncate(half);
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use super::{insert_bit, pairs, MultilinearPoly};
-    use super::{ProductPoly, SumPoly};
+    use super::{eq_eval, insert_bit, pairs, MultilinearPoly};
+    use super::{PaddedMultilinearPoly, Padding, ProductPoly, SumPoly, VariableOrder};
+    use crate::libs::fiat_shamir::Transcript;
     use ark_bn254::{Fq, Fr};
 
     pub(crate) fn to_field(input: Vec<u64>) -> Vec<Fr> {
-        input.into_iter().map(|v| Fr::from(v)).collect()
+        input.into_iter().map(Fr::from).collect()
+    }
+
+    // a transcript that has already absorbed the prover's commitment, named by label
+    pub(crate) fn committed_transcript(label: &[u8]) -> Transcript<Fr> {
+        let mut transcript = Transcript::new();
+        transcript.append(label);
+        transcript
+    }
+
+    #[test]
+    fn eq_table_selects_evaluations() {
+        let point = to_field(vec![3, 5, 7]);
+        let poly = MultilinearPoly::new(to_field(vec![1, 2, 3, 4, 5, 6, 7, 8]));
+        let eq = MultilinearPoly::eq(&point);
+
+        let sum: Fr = eq
+            .evals
+            .iter()
+            .zip(poly.evals.iter())
+            .map(|(e, v)| *e * v)
+            .sum();
+
+        assert_eq!(sum, poly.evaluate(point.clone()));
+        assert_eq!(
+            eq.evaluate(to_field(vec![2, 4, 6])),
+            eq_eval(&point, &to_field(vec![2, 4, 6]))
+        );
+
+        // a boolean point picks out one index
+        let mut indicator = vec![Fr::from(0); 8];
+        indicator[0b110] = Fr::from(1);
+        assert_eq!(
+            MultilinearPoly::eq(&to_field(vec![1, 1, 0])).evals,
+            indicator
+        );
     }
 
     #[test]
@@ -302,6 +723,7 @@ pub(crate) mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn test_pairs() {
         // 0 - 4
         // 1 - 5
@@ -322,6 +744,148 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn it_indexes_variable_zero_as_the_high_bit() {
+        // 2ab + 3bc
+        let poly = MultilinearPoly::new(to_field(vec![0_u64, 0, 0, 3, 0, 0, 2, 5]));
+
+        assert_eq!(poly.index_of(&[false, true, true]), 0b011);
+        assert_eq!(poly.get(&[false, true, true]), Fr::from(3));
+        assert_eq!(poly.get(&[true, true, false]), Fr::from(2));
+    }
+
+    #[test]
+    fn it_converts_between_variable_orders() {
+        // f(a, b) = a + 2b: MsbFirst [f(00), f(01), f(10), f(11)] = [0, 2, 1, 3]
+        let msb = to_field(vec![0, 2, 1, 3]);
+        let lsb = to_field(vec![0, 1, 2, 3]);
+
+        let poly = MultilinearPoly::from_evals_with_order(lsb.clone(), VariableOrder::LsbFirst);
+
+        assert_eq!(poly.evals, msb);
+        assert_eq!(poly.evals_with_order(VariableOrder::LsbFirst), lsb);
+        assert_eq!(poly.evals_with_order(VariableOrder::MsbFirst), msb);
+    }
+
+    #[test]
+    fn permuted_poly_evaluates_at_permuted_point() {
+        let poly = MultilinearPoly::new(to_field(vec![0_u64, 0, 0, 3, 0, 0, 2, 5]));
+        let permutation = [2, 0, 1];
+        let values = to_field(vec![4, 7, 9]);
+
+        // new variable i takes the value of old variable permutation[i]
+        let mut old_values = vec![Fr::from(0); 3];
+        for (new_var, old_var) in permutation.iter().enumerate() {
+            old_values[*old_var] = values[new_var];
+        }
+
+        let permuted = poly.permute_variables(&permutation);
+
+        assert_eq!(permuted.evaluate(values), poly.evaluate(old_values));
+        assert_eq!(poly.reverse_variables().reverse_variables(), poly);
+        assert_eq!(
+            poly.reverse_variables().evaluate(to_field(vec![9, 7, 4])),
+            poly.evaluate(to_field(vec![4, 7, 9]))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn permute_variables_rejects_repeated_variables() {
+        let poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3]));
+
+        let _ = poly.permute_variables(&[1, 1]);
+    }
+
+    #[test]
+    fn it_converts_between_evaluations_and_coefficients() {
+        // 2ab + 3bc
+        let poly = MultilinearPoly::new(to_field(vec![0_u64, 0, 0, 3, 0, 0, 2, 5]));
+
+        assert_eq!(
+            poly.to_coefficients(),
+            to_field(vec![0, 0, 0, 3, 0, 0, 2, 0])
+        );
+        assert_eq!(
+            MultilinearPoly::from_coefficients(poly.to_coefficients()),
+            poly
+        );
+        assert_eq!(
+            MultilinearPoly::from_monomials(
+                3,
+                &[(Fr::from(2), vec![0, 1]), (Fr::from(3), vec![1, 2])]
+            ),
+            poly
+        );
+    }
+
+    #[test]
+    fn it_prints_the_monomial_form() {
+        let poly = MultilinearPoly::new(to_field(vec![0_u64, 0, 0, 3, 0, 0, 2, 5]));
+        assert_eq!(poly.to_string(), "3bc + 2ab");
+
+        // 4 - a + abc
+        let poly = MultilinearPoly::from_monomials(
+            3,
+            &[
+                (Fr::from(4), vec![]),
+                (-Fr::from(1), vec![0]),
+                (Fr::from(1), vec![0, 1, 2]),
+            ],
+        );
+        assert_eq!(poly.to_string(), "4 - a + abc");
+
+        let zero = MultilinearPoly::new(vec![Fr::from(0); 4]);
+        assert_eq!(zero.to_string(), "0");
+    }
+
+    #[test]
+    fn it_pads_to_the_next_power_of_two() {
+        let zeros = PaddedMultilinearPoly::new(to_field(vec![1, 2, 3, 4, 5]), Padding::Zeros);
+        assert_eq!(zeros.poly().n_vars, 3);
+        assert_eq!(zeros.logical_len(), 5);
+        assert_eq!(zeros.poly().evals, to_field(vec![1, 2, 3, 4, 5, 0, 0, 0]));
+
+        let repeat = PaddedMultilinearPoly::new(to_field(vec![1, 2, 3]), Padding::RepeatLast);
+        assert_eq!(repeat.poly().evals, to_field(vec![1, 2, 3, 3]));
+        assert_eq!(repeat.logical_evals(), to_field(vec![1, 2, 3]).as_slice());
+
+        let value =
+            PaddedMultilinearPoly::new(to_field(vec![1, 2, 3]), Padding::Value(Fr::from(7)));
+        assert_eq!(value.poly().evals, to_field(vec![1, 2, 3, 7]));
+    }
+
+    #[test]
+    fn padded_poly_accounts_for_padding_in_sums() {
+        let padded =
+            PaddedMultilinearPoly::new(to_field(vec![1, 2, 3]), Padding::Value(Fr::from(7)));
+
+        assert_eq!(padded.logical_sum(), Fr::from(6));
+        assert_eq!(padded.padding_sum(), Fr::from(7));
+        assert_eq!(
+            padded.hypercube_sum(),
+            padded.poly().evals.iter().sum::<Fr>()
+        );
+    }
+
+    #[test]
+    fn it_pads_to_a_requested_number_of_variables() {
+        let padded = PaddedMultilinearPoly::with_n_vars(to_field(vec![27]), Padding::Zeros, 1);
+
+        assert_eq!(padded.poly().evals, to_field(vec![27, 0]));
+        assert_eq!(padded.logical_len(), 1);
+
+        let exact = PaddedMultilinearPoly::new(to_field(vec![1, 2]), Padding::RepeatLast);
+        assert_eq!(exact.poly().evals, to_field(vec![1, 2]));
+        assert_eq!(exact.padding_sum(), Fr::from(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn padded_poly_rejects_too_few_variables() {
+        let _ = PaddedMultilinearPoly::with_n_vars(to_field(vec![1, 2, 3]), Padding::Zeros, 1);
+    }
+
     #[test]
     fn product_poly_evaluates_multiple_polys() {
         let evaluations = vec![
@@ -391,7 +955,20 @@ pub(crate) mod tests {
     fn product_poly_gets_correct_degree() {}
 
     #[test]
-    fn sum_poly_gets_correct_degree() {}
+    fn sum_poly_gets_correct_degree() {
+        let linear = ProductPoly::new(vec![vec![Fq::from(1), Fq::from(2)]]);
+        let cubic = ProductPoly::new(vec![
+            vec![Fq::from(1), Fq::from(2)],
+            vec![Fq::from(3), Fq::from(4)],
+            vec![Fq::from(5), Fq::from(6)],
+        ]);
+
+        let sum_poly = SumPoly::new(vec![linear, cubic]);
+
+        assert_eq!(sum_poly.get_degree(), 3);
+        // [1 + 15, 2 + 48]
+        assert_eq!(sum_poly.reduce(), vec![Fq::from(16), Fq::from(50)]);
+    }
 
     #[test]
     fn sum_poly_evaluates_properly() {
@@ -0,0 +1,449 @@
+use super::fiat_shamir::Transcript;
+use super::zk_sum_check::HomomorphicCommitment;
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ec::{AffineRepr, VariableBaseMSM};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::Rng;
+use sha3::{Digest, Keccak256};
+
+// sum v_i * G_i + blind * H. every generator is hashed to the curve, so nobody knows a
+// discrete log relation between them and the commitment is binding as well as hiding
+#[derive(Clone, Debug)]
+pub struct PedersenParams<P: SWCurveConfig> {
+    pub(crate) generators: Vec<Affine<P>>,
+    pub(crate) blinding_generator: Affine<P>,
+}
+
+// a committed value together with the blind the prover keeps to itself
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Opening<F> {
+    pub value: F,
+    pub blind: F,
+}
+
+impl<F: UniformRand> Opening<F> {
+    pub fn random<R: Rng>(value: F, rng: &mut R) -> Self {
+        Self {
+            value,
+            blind: F::rand(rng),
+        }
+    }
+}
+
+impl<P: SWCurveConfig> PedersenParams<P> {
+    // label separates the generators of different applications
+    pub fn new(max_len: usize, label: &[u8]) -> Self {
+        Self {
+            generators: (0..max_len as u64)
+                .map(|i| hash_to_curve(label, i))
+                .collect(),
+            blinding_generator: hash_to_curve(label, u64::MAX),
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.generators.len()
+    }
+
+    pub fn commit(&self, values: &[P::ScalarField], blind: P::ScalarField) -> Projective<P> {
+        if values.len() > self.max_len() {
+            panic!("more values than generators");
+        }
+
+        Projective::<P>::msm(&self.generators[..values.len()], values).unwrap()
+            + self.blinding_generator * blind
+    }
+
+    // a single value sits on the first generator
+    pub fn commit_opening(&self, opening: &Opening<P::ScalarField>) -> Projective<P> {
+        self.commit(&[opening.value], opening.blind)
+    }
+
+    pub fn check_opening(
+        &self,
+        commitment: &Projective<P>,
+        opening: &Opening<P::ScalarField>,
+    ) -> bool {
+        self.commit_opening(opening) == *commitment
+    }
+}
+
+// try-and-increment on x, then clear the cofactor
+fn hash_to_curve<P: SWCurveConfig>(label: &[u8], index: u64) -> Affine<P> {
+    for counter in 0u64.. {
+        let mut hasher = Keccak256::new();
+        hasher.update(label);
+        hasher.update(index.to_le_bytes());
+        hasher.update(counter.to_le_bytes());
+        let digest = hasher.finalize();
+
+        let point = P::BaseField::from_random_bytes(&digest)
+            .and_then(|x| Affine::<P>::get_point_from_x_unchecked(x, digest[0] & 1 == 1))
+            .map(|point| point.clear_cofactor());
+
+        if let Some(point) = point.filter(|point| !point.is_zero()) {
+            return point;
+        }
+    }
+
+    unreachable!()
+}
+
+pub fn points_to_bytes<P: SWCurveConfig>(points: &[Projective<P>]) -> Vec<u8> {
+    let mut bytes = vec![];
+    for point in points {
+        point.serialize_compressed(&mut bytes).unwrap();
+    }
+
+    bytes
+}
+
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b.iter()).map(|(a, b)| *a * b).sum()
+}
+
+// left and right commit to the same value, i.e. left - right is a multiple of H alone
+#[derive(Clone, Debug, PartialEq)]
+pub struct EqualityProof<P: SWCurveConfig> {
+    pub(crate) announcement: Projective<P>,
+    pub(crate) response: P::ScalarField,
+}
+
+impl<P: SWCurveConfig> EqualityProof<P> {
+    pub fn prove<R: Rng>(
+        params: &PedersenParams<P>,
+        left: &Opening<P::ScalarField>,
+        right: &Opening<P::ScalarField>,
+        transcript: &mut Transcript<P::ScalarField>,
+        rng: &mut R,
+    ) -> Self {
+        if left.value != right.value {
+            panic!("the openings hold different values");
+        }
+
+        let nonce = P::ScalarField::rand(rng);
+        let announcement = params.blinding_generator * nonce;
+
+        let challenge = Self::challenge(
+            &params.commit_opening(left),
+            &params.commit_opening(right),
+            &announcement,
+            transcript,
+        );
+
+        Self {
+            announcement,
+            response: nonce + challenge * (left.blind - right.blind),
+        }
+    }
+
+    pub fn verify(
+        &self,
+        params: &PedersenParams<P>,
+        left: &Projective<P>,
+        right: &Projective<P>,
+        transcript: &mut Transcript<P::ScalarField>,
+    ) -> bool {
+        let challenge = Self::challenge(left, right, &self.announcement, transcript);
+
+        params.blinding_generator * self.response == self.announcement + (*left - right) * challenge
+    }
+
+    fn challenge(
+        left: &Projective<P>,
+        right: &Projective<P>,
+        announcement: &Projective<P>,
+        transcript: &mut Transcript<P::ScalarField>,
+    ) -> P::ScalarField {
+        transcript.append(&points_to_bytes(&[*left, *right, *announcement]));
+
+        transcript.get_random_challenge()
+    }
+}
+
+// product commits to x * y, where x and y are the values of the other two commitments
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProductProof<P: SWCurveConfig> {
+    pub(crate) announcements: [Projective<P>; 3],
+    pub(crate) responses: [P::ScalarField; 5],
+}
+
+impl<P: SWCurveConfig> ProductProof<P> {
+    pub fn prove<R: Rng>(
+        params: &PedersenParams<P>,
+        x: &Opening<P::ScalarField>,
+        y: &Opening<P::ScalarField>,
+        product: &Opening<P::ScalarField>,
+        transcript: &mut Transcript<P::ScalarField>,
+        rng: &mut R,
+    ) -> Self {
+        if x.value * y.value != product.value {
+            panic!("the product opening does not hold x * y");
+        }
+
+        let commitments = [x, y, product].map(|opening| params.commit_opening(opening));
+        let nonces: [P::ScalarField; 5] = std::array::from_fn(|_| P::ScalarField::rand(rng));
+
+        // the last one reuses x's commitment as its base, which ties the product to x
+        let announcements = [
+            params.commit(&[nonces[0]], nonces[1]),
+            params.commit(&[nonces[2]], nonces[3]),
+            commitments[0] * nonces[2] + params.blinding_generator * nonces[4],
+        ];
+
+        let c = Self::challenge(&commitments, &announcements, transcript);
+
+        Self {
+            announcements,
+            responses: [
+                nonces[0] + c * x.value,
+                nonces[1] + c * x.blind,
+                nonces[2] + c * y.value,
+                nonces[3] + c * y.blind,
+                nonces[4] + c * (product.blind - x.blind * y.value),
+            ],
+        }
+    }
+
+    pub fn verify(
+        &self,
+        params: &PedersenParams<P>,
+        x: &Projective<P>,
+        y: &Projective<P>,
+        product: &Projective<P>,
+        transcript: &mut Transcript<P::ScalarField>,
+    ) -> bool {
+        let c = Self::challenge(&[*x, *y, *product], &self.announcements, transcript);
+        let [a, b, d] = self.announcements;
+        let z = self.responses;
+
+        params.commit(&[z[0]], z[1]) == a + *x * c
+            && params.commit(&[z[2]], z[3]) == b + *y * c
+            && *x * z[2] + params.blinding_generator * z[4] == d + *product * c
+    }
+
+    fn challenge(
+        commitments: &[Projective<P>; 3],
+        announcements: &[Projective<P>; 3],
+        transcript: &mut Transcript<P::ScalarField>,
+    ) -> P::ScalarField {
+        transcript.append(&points_to_bytes(commitments));
+        transcript.append(&points_to_bytes(announcements));
+
+        transcript.get_random_challenge()
+    }
+}
+
+// a committed vector v and a committed y with <v, a> = y for a public a. nothing about v
+// leaks beyond that, so a commitment to a table opens at a point as a commitment again
+#[derive(Clone, Debug, PartialEq)]
+pub struct DotProductProof<P: SWCurveConfig> {
+    pub(crate) announcements: [Projective<P>; 2],
+    pub(crate) response: Vec<P::ScalarField>,
+    pub(crate) response_blinds: [P::ScalarField; 2],
+}
+
+impl<P: SWCurveConfig> DotProductProof<P> {
+    pub fn prove<R: Rng>(
+        params: &PedersenParams<P>,
+        values: &[P::ScalarField],
+        blind: P::ScalarField,
+        a: &[P::ScalarField],
+        y: &Opening<P::ScalarField>,
+        transcript: &mut Transcript<P::ScalarField>,
+        rng: &mut R,
+    ) -> Self {
+        if values.len() != a.len() || inner_product(values, a) != y.value {
+            panic!("y is not <values, a>");
+        }
+
+        let nonce: Vec<P::ScalarField> = (0..values.len())
+            .map(|_| P::ScalarField::rand(rng))
+            .collect();
+        let nonce_blinds = [P::ScalarField::rand(rng), P::ScalarField::rand(rng)];
+
+        let announcements = [
+            params.commit(&nonce, nonce_blinds[0]),
+            params.commit(&[inner_product(&nonce, a)], nonce_blinds[1]),
+        ];
+
+        let c = Self::challenge(
+            &[params.commit(values, blind), params.commit_opening(y)],
+            &announcements,
+            transcript,
+        );
+
+        Self {
+            announcements,
+            response: values
+                .iter()
+                .zip(nonce.iter())
+                .map(|(v, d)| c * v + d)
+                .collect(),
+            response_blinds: [c * blind + nonce_blinds[0], c * y.blind + nonce_blinds[1]],
+        }
+    }
+
+    pub fn verify(
+        &self,
+        params: &PedersenParams<P>,
+        commitment: &Projective<P>,
+        y: &Projective<P>,
+        a: &[P::ScalarField],
+        transcript: &mut Transcript<P::ScalarField>,
+    ) -> bool {
+        if self.response.len() != a.len() || a.len() > params.max_len() {
+            return false;
+        }
+
+        let c = Self::challenge(&[*commitment, *y], &self.announcements, transcript);
+
+        params.commit(&self.response, self.response_blinds[0])
+            == self.announcements[0] + *commitment * c
+            && params.commit(&[inner_product(&self.response, a)], self.response_blinds[1])
+                == self.announcements[1] + *y * c
+    }
+
+    fn challenge(
+        commitments: &[Projective<P>; 2],
+        announcements: &[Projective<P>; 2],
+        transcript: &mut Transcript<P::ScalarField>,
+    ) -> P::ScalarField {
+        transcript.append(&points_to_bytes(commitments));
+        transcript.append(&points_to_bytes(announcements));
+
+        transcript.get_random_challenge()
+    }
+}
+
+// a public value as a commitment with no blind, so it can stand in for a committed one
+pub fn public_commitment<P: SWCurveConfig>(
+    params: &PedersenParams<P>,
+    value: P::ScalarField,
+) -> Projective<P> {
+    params.commit(&[value], P::ScalarField::zero())
+}
+
+impl<P: SWCurveConfig> HomomorphicCommitment<P::ScalarField> for PedersenParams<P> {
+    type Commitment = Projective<P>;
+    type DotProductProof = DotProductProof<P>;
+
+    fn commit(&self, values: &[P::ScalarField], blind: P::ScalarField) -> Projective<P> {
+        PedersenParams::commit(self, values, blind)
+    }
+
+    fn commitment_to_bytes(commitments: &[Projective<P>]) -> Vec<u8> {
+        points_to_bytes(commitments)
+    }
+
+    fn prove_dot_product<R: Rng>(
+        &self,
+        values: &[P::ScalarField],
+        blind: P::ScalarField,
+        a: &[P::ScalarField],
+        y: &Opening<P::ScalarField>,
+        transcript: &mut Transcript<P::ScalarField>,
+        rng: &mut R,
+    ) -> DotProductProof<P> {
+        DotProductProof::prove(self, values, blind, a, y, transcript, rng)
+    }
+
+    fn verify_dot_product(
+        &self,
+        proof: &DotProductProof<P>,
+        commitment: &Projective<P>,
+        y: &Projective<P>,
+        a: &[P::ScalarField],
+        transcript: &mut Transcript<P::ScalarField>,
+    ) -> bool {
+        proof.verify(self, commitment, y, a, transcript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DotProductProof, EqualityProof, Opening, PedersenParams, ProductProof};
+    use crate::libs::fiat_shamir::Transcript;
+    use ark_bn254::{g1::Config, Fr};
+    use ark_ff::One;
+    use ark_std::test_rng;
+
+    fn params() -> PedersenParams<Config> {
+        PedersenParams::new(4, b"pedersen tests")
+    }
+
+    #[test]
+    fn it_commits_homomorphically() {
+        let params = params();
+        let a = [Fr::from(1), Fr::from(2)];
+        let b = [Fr::from(5), Fr::from(7)];
+
+        assert_eq!(
+            params.commit(&a, Fr::from(3)) + params.commit(&b, Fr::from(4)),
+            params.commit(&[Fr::from(6), Fr::from(9)], Fr::from(7))
+        );
+        assert_ne!(
+            params.commit(&a, Fr::from(3)),
+            params.commit(&a, Fr::from(4))
+        );
+        assert_ne!(params.generators[0], params.generators[1]);
+    }
+
+    #[test]
+    fn it_proves_equality_and_products() {
+        let params = params();
+        let mut rng = test_rng();
+
+        let x = Opening::random(Fr::from(6), &mut rng);
+        let y = Opening::random(Fr::from(7), &mut rng);
+        let product = Opening::random(Fr::from(42), &mut rng);
+        let same = Opening::random(Fr::from(6), &mut rng);
+        let [c_x, c_y, c_product, c_same] =
+            [x, y, product, same].map(|opening| params.commit_opening(&opening));
+
+        let proof = EqualityProof::prove(&params, &x, &same, &mut Transcript::new(), &mut rng);
+        assert!(proof.verify(&params, &c_x, &c_same, &mut Transcript::new()));
+        assert!(!proof.verify(&params, &c_x, &c_y, &mut Transcript::new()));
+
+        let proof =
+            ProductProof::prove(&params, &x, &y, &product, &mut Transcript::new(), &mut rng);
+        assert!(proof.verify(&params, &c_x, &c_y, &c_product, &mut Transcript::new()));
+        assert!(!proof.verify(&params, &c_x, &c_x, &c_product, &mut Transcript::new()));
+        assert!(!proof.verify(&params, &c_x, &c_y, &c_same, &mut Transcript::new()));
+    }
+
+    #[test]
+    fn it_proves_a_dot_product() {
+        let params = params();
+        let mut rng = test_rng();
+
+        let values = [Fr::from(1), Fr::from(2), Fr::from(3)];
+        let a = [Fr::from(4), Fr::from(5), Fr::from(6)];
+        let blind = Fr::from(11);
+        let y = Opening::random(Fr::from(32), &mut rng);
+
+        let commitment = params.commit(&values, blind);
+        let c_y = params.commit_opening(&y);
+
+        let proof = DotProductProof::prove(
+            &params,
+            &values,
+            blind,
+            &a,
+            &y,
+            &mut Transcript::new(),
+            &mut rng,
+        );
+        assert!(proof.verify(&params, &commitment, &c_y, &a, &mut Transcript::new()));
+
+        let other_a = [Fr::from(4), Fr::from(5), Fr::from(7)];
+        assert!(!proof.verify(&params, &commitment, &c_y, &other_a, &mut Transcript::new()));
+
+        let wrong_y = params.commit(&[Fr::from(33)], y.blind);
+        assert!(!proof.verify(&params, &commitment, &wrong_y, &a, &mut Transcript::new()));
+
+        let short_a = [Fr::one(), Fr::one()];
+        assert!(!proof.verify(&params, &commitment, &c_y, &short_a, &mut Transcript::new()));
+    }
+}
@@ -0,0 +1,310 @@
+use super::fiat_shamir::Transcript;
+use super::grand_product::{self, GrandProductError, GrandProductProof};
+use super::multilinear_poly::MultilinearPoly;
+use super::sum_check_protocol::EvaluationClaim;
+use ark_ff::PrimeField;
+use std::fmt;
+
+// {a_i} = {b_i} as multisets iff prod (gamma - a_i) = prod (gamma - b_i) for a random gamma,
+// and both products come from grand_product. with a public sigma the entries are tagged
+// with their position first, (i, a_i) against (sigma(i), b_i), which holds iff
+// b_i = a_sigma(i). the transcript should already bind a and b, e.g. hold their commitments
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisetProof<F: PrimeField> {
+    pub left: GrandProductProof<F>,
+    pub right: GrandProductProof<F>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MultisetError {
+    Left(GrandProductError),
+    Right(GrandProductError),
+    ProductMismatch,
+}
+
+impl fmt::Display for MultisetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultisetError::Left(error) => write!(f, "left product: {}", error),
+            MultisetError::Right(error) => write!(f, "right product: {}", error),
+            MultisetError::ProductMismatch => write!(f, "fingerprints differ"),
+        }
+    }
+}
+
+impl std::error::Error for MultisetError {}
+
+// gamma - (tag + beta * value), with the tag left out when there is none
+struct Fingerprint<F: PrimeField> {
+    gamma: F,
+    beta: F,
+}
+
+impl<F: PrimeField> Fingerprint<F> {
+    fn new(transcript: &mut Transcript<F>) -> Self {
+        Self {
+            gamma: transcript.get_random_challenge(),
+            beta: transcript.get_random_challenge(),
+        }
+    }
+
+    fn apply(&self, values: &[F], tags: Option<&[F]>) -> MultilinearPoly<F> {
+        MultilinearPoly::new(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let tag = tags.map_or(F::zero(), |tags| tags[i]);
+                    self.gamma - (tag + self.beta * value)
+                })
+                .collect(),
+        )
+    }
+
+    // turns a claim on the fingerprints back into one on the values
+    fn invert(&self, claim: EvaluationClaim<F>, tag: F) -> EvaluationClaim<F> {
+        let value = (self.gamma - tag - claim.value) * self.beta.inverse().unwrap();
+
+        EvaluationClaim {
+            point: claim.point,
+            value,
+        }
+    }
+}
+
+// i as a field element for every index, the tags of the left table
+fn identity<F: PrimeField>(n_vars: usize) -> Vec<F> {
+    (0..1u64 << n_vars).map(F::from).collect()
+}
+
+// sum r_j * 2^(n - 1 - j), the multilinear extension of identity at r
+fn identity_at<F: PrimeField>(point: &[F]) -> F {
+    point.iter().fold(F::zero(), |acc, r| acc.double() + r)
+}
+
+fn sigma_table<F: PrimeField>(sigma: &[usize]) -> Vec<F> {
+    let mut seen = vec![false; sigma.len()];
+    for i in sigma {
+        if *i >= sigma.len() || seen[*i] {
+            panic!("sigma is not a permutation");
+        }
+        seen[*i] = true;
+    }
+
+    sigma.iter().map(|i| F::from(*i as u64)).collect()
+}
+
+fn prove_fingerprints<F: PrimeField>(
+    left: &MultilinearPoly<F>,
+    right: &MultilinearPoly<F>,
+    tags: Option<(&[F], &[F])>,
+    transcript: &mut Transcript<F>,
+) -> (MultisetProof<F>, EvaluationClaim<F>, EvaluationClaim<F>) {
+    if left.n_vars != right.n_vars {
+        panic!("tables must have the same number of variables");
+    }
+
+    let fingerprint = Fingerprint::new(transcript);
+
+    let (left_proof, left_claim) = grand_product::prove(
+        &fingerprint.apply(&left.evals, tags.map(|tags| tags.0)),
+        transcript,
+    );
+    let (right_proof, right_claim) = grand_product::prove(
+        &fingerprint.apply(&right.evals, tags.map(|tags| tags.1)),
+        transcript,
+    );
+
+    let (left_tag, right_tag) = match tags {
+        Some((left_tags, right_tags)) => (
+            MultilinearPoly::new(left_tags.to_vec()).evaluate(left_claim.point.clone()),
+            MultilinearPoly::new(right_tags.to_vec()).evaluate(right_claim.point.clone()),
+        ),
+        None => (F::zero(), F::zero()),
+    };
+
+    let proof = MultisetProof {
+        left: left_proof,
+        right: right_proof,
+    };
+
+    (
+        proof,
+        fingerprint.invert(left_claim, left_tag),
+        fingerprint.invert(right_claim, right_tag),
+    )
+}
+
+// tag_at evaluates the left and right tags at the left and right points
+fn verify_fingerprints<F: PrimeField, T: Fn(&[F], &[F]) -> (F, F)>(
+    proof: &MultisetProof<F>,
+    n_vars: usize,
+    tag_at: T,
+    transcript: &mut Transcript<F>,
+) -> Result<(EvaluationClaim<F>, EvaluationClaim<F>), MultisetError> {
+    let fingerprint = Fingerprint::new(transcript);
+
+    let left_claim =
+        grand_product::verify(&proof.left, n_vars, transcript).map_err(MultisetError::Left)?;
+    let right_claim =
+        grand_product::verify(&proof.right, n_vars, transcript).map_err(MultisetError::Right)?;
+
+    if proof.left.product != proof.right.product {
+        return Err(MultisetError::ProductMismatch);
+    }
+
+    let (left_tag, right_tag) = tag_at(&left_claim.point, &right_claim.point);
+
+    Ok((
+        fingerprint.invert(left_claim, left_tag),
+        fingerprint.invert(right_claim, right_tag),
+    ))
+}
+
+// the returned claims are on a and b, still to be checked against their commitments
+pub fn prove_multiset_equality<F: PrimeField>(
+    a: &MultilinearPoly<F>,
+    b: &MultilinearPoly<F>,
+    transcript: &mut Transcript<F>,
+) -> (MultisetProof<F>, EvaluationClaim<F>, EvaluationClaim<F>) {
+    prove_fingerprints(a, b, None, transcript)
+}
+
+pub fn verify_multiset_equality<F: PrimeField>(
+    proof: &MultisetProof<F>,
+    n_vars: usize,
+    transcript: &mut Transcript<F>,
+) -> Result<(EvaluationClaim<F>, EvaluationClaim<F>), MultisetError> {
+    verify_fingerprints(proof, n_vars, |_, _| (F::zero(), F::zero()), transcript)
+}
+
+// b_i = a_sigma(i) for every i. with a = b this is a copy constraint: every cycle of sigma
+// has to hold one value
+pub fn prove_permutation<F: PrimeField>(
+    a: &MultilinearPoly<F>,
+    b: &MultilinearPoly<F>,
+    sigma: &[usize],
+    transcript: &mut Transcript<F>,
+) -> (MultisetProof<F>, EvaluationClaim<F>, EvaluationClaim<F>) {
+    if sigma.len() != a.evals.len() {
+        panic!("sigma must have one entry per value");
+    }
+
+    let identity = identity(a.n_vars);
+    let sigma = sigma_table(sigma);
+
+    prove_fingerprints(a, b, Some((&identity, &sigma)), transcript)
+}
+
+// sigma is public, so the verifier evaluates its extension itself in O(len)
+pub fn verify_permutation<F: PrimeField>(
+    proof: &MultisetProof<F>,
+    sigma: &[usize],
+    transcript: &mut Transcript<F>,
+) -> Result<(EvaluationClaim<F>, EvaluationClaim<F>), MultisetError> {
+    let sigma = MultilinearPoly::new(sigma_table(sigma));
+
+    verify_fingerprints(
+        proof,
+        sigma.n_vars,
+        |left_point, right_point| {
+            (
+                identity_at(left_point),
+                sigma.evaluate(right_point.to_vec()),
+            )
+        },
+        transcript,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        prove_multiset_equality, prove_permutation, verify_multiset_equality, verify_permutation,
+        MultisetError,
+    };
+    use crate::libs::multilinear_poly::tests::{committed_transcript, to_field};
+    use crate::libs::multilinear_poly::MultilinearPoly;
+
+    const COMMITMENTS: &[u8] = b"commitments to a and b";
+
+    #[test]
+    fn it_checks_multiset_equality() {
+        let a = MultilinearPoly::new(to_field(vec![3, 1, 4, 1, 5, 9, 2, 6]));
+        let b = MultilinearPoly::new(to_field(vec![1, 9, 6, 3, 1, 2, 5, 4]));
+
+        let (proof, a_claim, b_claim) =
+            prove_multiset_equality(&a, &b, &mut committed_transcript(COMMITMENTS));
+        let claims =
+            verify_multiset_equality(&proof, 3, &mut committed_transcript(COMMITMENTS)).unwrap();
+
+        assert_eq!(claims, (a_claim, b_claim));
+        assert!(claims.0.check(|point| a.evaluate(point.to_vec())));
+        assert!(claims.1.check(|point| b.evaluate(point.to_vec())));
+
+        // one 1 replaced by a 7
+        let c = MultilinearPoly::new(to_field(vec![1, 9, 6, 3, 7, 2, 5, 4]));
+        let (proof, _, _) = prove_multiset_equality(&a, &c, &mut committed_transcript(COMMITMENTS));
+        assert_eq!(
+            verify_multiset_equality(&proof, 3, &mut committed_transcript(COMMITMENTS)),
+            Err(MultisetError::ProductMismatch)
+        );
+    }
+
+    #[test]
+    fn it_checks_a_public_permutation() {
+        let a = MultilinearPoly::new(to_field(vec![3, 1, 4, 1]));
+        let sigma = [2, 0, 3, 1];
+        // b_i = a_sigma(i)
+        let b = MultilinearPoly::new(to_field(vec![4, 3, 1, 1]));
+
+        let (proof, a_claim, b_claim) =
+            prove_permutation(&a, &b, &sigma, &mut committed_transcript(COMMITMENTS));
+        let claims =
+            verify_permutation(&proof, &sigma, &mut committed_transcript(COMMITMENTS)).unwrap();
+
+        assert_eq!(claims, (a_claim, b_claim));
+        assert!(claims.0.check(|point| a.evaluate(point.to_vec())));
+        assert!(claims.1.check(|point| b.evaluate(point.to_vec())));
+
+        // same multiset, wrong order
+        let shuffled = MultilinearPoly::new(to_field(vec![3, 4, 1, 1]));
+        let (proof, _, _) = prove_permutation(
+            &a,
+            &shuffled,
+            &sigma,
+            &mut committed_transcript(COMMITMENTS),
+        );
+        assert_eq!(
+            verify_permutation(&proof, &sigma, &mut committed_transcript(COMMITMENTS)),
+            Err(MultisetError::ProductMismatch)
+        );
+    }
+
+    #[test]
+    fn it_checks_copy_constraints() {
+        // wires 0, 3 and 5 are one value, so are 1 and 6
+        let sigma = [3, 6, 2, 5, 4, 0, 1, 7];
+        let wires = MultilinearPoly::new(to_field(vec![8, 2, 7, 8, 1, 8, 2, 0]));
+
+        let (proof, _, _) = prove_permutation(
+            &wires,
+            &wires,
+            &sigma,
+            &mut committed_transcript(COMMITMENTS),
+        );
+        assert!(verify_permutation(&proof, &sigma, &mut committed_transcript(COMMITMENTS)).is_ok());
+
+        let broken = MultilinearPoly::new(to_field(vec![8, 2, 7, 8, 1, 9, 2, 0]));
+        let (proof, _, _) = prove_permutation(
+            &broken,
+            &broken,
+            &sigma,
+            &mut committed_transcript(COMMITMENTS),
+        );
+        assert_eq!(
+            verify_permutation(&proof, &sigma, &mut committed_transcript(COMMITMENTS)),
+            Err(MultisetError::ProductMismatch)
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use ark_ff::PrimeField;
+use ark_ff::{Field, PrimeField};
 use std::{
-    iter::{Product, Sum},
+    iter::Sum,
     ops::{Add, Mul},
 };
 
@@ -49,13 +49,11 @@ impl<F: PrimeField> UnivariatePolynomial<F> {
             let (x_i, y_i) = points[i];
             let mut l_i = UnivariatePolynomial::new(vec![F::one()]);
 
-            for j in 0..n {
+            for (j, (x_j, _)) in points.iter().enumerate() {
                 if i != j {
-                    let (x_j, _) = points[j];
+                    let numerator = UnivariatePolynomial::new(vec![-*x_j, F::one()]);
 
-                    let numerator = UnivariatePolynomial::new(vec![-x_j, F::one()]);
-
-                    let denominator = x_i - x_j;
+                    let denominator = x_i - *x_j;
 
                     l_i = l_i * numerator.scalar_mul(F::one() / denominator);
                 }
@@ -105,6 +103,58 @@ impl<F: PrimeField> UnivariatePolynomial<F> {
     // }
 }
 
+// evaluates the degree evals.len() - 1 polynomial through (i, evals[i]) for i = 0, 1, ...
+// at x without interpolating: p(x) = l(x) * sum w_i * y_i / (x - i), l(x) = prod (x - i)
+// and w_i = 1 / prod_{j != i} (i - j) = (-1)^(d - i) / (i! * (d - i)!)
+pub fn barycentric_evaluate<F: Field>(evals: &[F], x: F) -> F {
+    let d = evals.len() - 1;
+
+    if let Some(i) = (0..=d).find(|i| F::from(*i as u64) == x) {
+        return evals[i];
+    }
+
+    let mut factorials = vec![F::one(); d + 1];
+    for i in 1..=d {
+        factorials[i] = factorials[i - 1] * F::from(i as u64);
+    }
+
+    let l_x: F = (0..=d).map(|i| x - F::from(i as u64)).product();
+
+    let sum: F = evals
+        .iter()
+        .enumerate()
+        .map(|(i, y)| {
+            let mut denominator = factorials[i] * factorials[d - i] * (x - F::from(i as u64));
+            if (d - i) % 2 == 1 {
+                denominator = -denominator;
+            }
+
+            *y * denominator.inverse().unwrap()
+        })
+        .sum();
+
+    l_x * sum
+}
+
+// the k lagrange basis polynomials over 0, 1, ..., k - 1 evaluated at x, so that
+// p(x) = sum basis[i] * p(i) for any p of degree below k
+pub fn lagrange_basis<F: Field>(k: usize, x: F) -> Vec<F> {
+    (0..k)
+        .map(|i| {
+            let xi = F::from(i as u64);
+            let (numerator, denominator) = (0..k).filter(|j| *j != i).fold(
+                (F::one(), F::one()),
+                |(numerator, denominator), j| {
+                    let xj = F::from(j as u64);
+                    (numerator * (x - xj), denominator * (xi - xj))
+                },
+            );
+
+            numerator * denominator.inverse().unwrap()
+        })
+        .collect()
+}
+
 // impl<F: PrimeField> Mul for &UnivariatePolynomial<F> {
 //     type Output = UnivariatePolynomial<F>;
 
@@ -126,7 +176,7 @@ impl<F: PrimeField> UnivariatePolynomial<F> {
 impl<F: PrimeField> Mul for UnivariatePolynomial<F> {
     type Output = Self;
 
-    fn mul(mut self, mut other: Self) -> Self {
+    fn mul(self, other: Self) -> Self {
         let mut coeffs = vec![F::zero(); self.degree() + other.degree() + 1];
 
         for (i, a) in self.coefficients.iter().enumerate() {
@@ -191,7 +241,7 @@ impl<F: PrimeField> Sum for UnivariatePolynomial<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::libs::polynomial::UnivariatePolynomial;
+    use crate::libs::polynomial::{barycentric_evaluate, lagrange_basis, UnivariatePolynomial};
     use ark_bn254::Fq;
 
     fn poly_1() -> UnivariatePolynomial<Fq> {
@@ -223,6 +273,41 @@ mod test {
         assert_eq!(poly_1().evaluate(Fq::from(2)), Fq::from(17));
     }
 
+    #[test]
+    fn barycentric_evaluation_matches_interpolation() {
+        // f(x) = 1 + 2x + 3x^2 at 0, 1, 2
+        let evals = vec![Fq::from(1), Fq::from(6), Fq::from(17)];
+
+        assert_eq!(barycentric_evaluate(&evals, Fq::from(5)), Fq::from(86));
+        assert_eq!(barycentric_evaluate(&evals, Fq::from(1)), Fq::from(6));
+        assert_eq!(
+            barycentric_evaluate(&poly_2_evals(), Fq::from(100)),
+            poly_2().evaluate(Fq::from(100))
+        );
+        assert_eq!(
+            barycentric_evaluate(&[Fq::from(7)], Fq::from(3)),
+            Fq::from(7)
+        );
+    }
+
+    #[test]
+    fn lagrange_basis_recombines_evaluations() {
+        let evals = poly_2_evals();
+        let basis = lagrange_basis(evals.len(), Fq::from(100));
+
+        let combined: Fq = basis.iter().zip(evals.iter()).map(|(l, y)| *l * y).sum();
+
+        assert_eq!(combined, poly_2().evaluate(Fq::from(100)));
+        assert_eq!(
+            lagrange_basis(3, Fq::from(1)),
+            vec![Fq::from(0), Fq::from(1), Fq::from(0)]
+        );
+    }
+
+    fn poly_2_evals() -> Vec<Fq> {
+        (0..12).map(|i| poly_2().evaluate(Fq::from(i))).collect()
+    }
+
     #[test]
     fn test_addition() {
         // f(x) = 1 + 2x + 3x^2
@@ -0,0 +1,200 @@
+use super::sparse_multilinear_poly::SparseMultilinearPoly;
+use ark_ff::PrimeField;
+
+// which column of z = (1, inputs, witness) a matrix entry multiplies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variable {
+    One,
+    Input(usize),
+    Witness(usize),
+}
+
+// (row, column, value) entries of a constraint matrix
+pub type Entries<F> = Vec<(usize, Variable, F)>;
+
+// (A z) * (B z) = C z row by row. the matrices are stored as sparse multilinear polys over
+// row bits || column bits, with the columns laid out as z = (witness, 1, inputs), each half
+// padded to the same power of two. the verifier can then evaluate z from the inputs and a
+// single claim on the witness: z(y) = (1 - y_0) * w(y_rest) + y_0 * (1, inputs)(y_rest)
+#[derive(Clone, Debug, PartialEq)]
+pub struct R1CS<F: PrimeField> {
+    pub(crate) a: SparseMultilinearPoly<F>,
+    pub(crate) b: SparseMultilinearPoly<F>,
+    pub(crate) c: SparseMultilinearPoly<F>,
+    pub(crate) num_constraints: usize,
+    pub(crate) num_inputs: usize,
+    pub(crate) num_witness: usize,
+    pub(crate) row_vars: usize,
+    pub(crate) col_vars: usize,
+}
+
+impl<F: PrimeField> R1CS<F> {
+    pub fn new(
+        num_constraints: usize,
+        num_inputs: usize,
+        num_witness: usize,
+        a: Entries<F>,
+        b: Entries<F>,
+        c: Entries<F>,
+    ) -> Self {
+        let row_vars = num_constraints.next_power_of_two().ilog2() as usize;
+        let half = num_witness.max(num_inputs + 1).next_power_of_two();
+        let col_vars = half.ilog2() as usize + 1;
+
+        let to_poly = |entries: Entries<F>| {
+            let entries = entries
+                .into_iter()
+                .map(|(row, variable, value)| {
+                    if row >= num_constraints {
+                        panic!("entry is outside the constraints");
+                    }
+
+                    let column = match variable {
+                        Variable::Witness(i) if i < num_witness => i,
+                        Variable::One => half,
+                        Variable::Input(i) if i < num_inputs => half + 1 + i,
+                        _ => panic!("entry refers to a variable that does not exist"),
+                    };
+
+                    ((row << col_vars) | column, value)
+                })
+                .collect();
+
+            SparseMultilinearPoly::new(row_vars + col_vars, entries)
+        };
+
+        Self {
+            a: to_poly(a),
+            b: to_poly(b),
+            c: to_poly(c),
+            num_constraints,
+            num_inputs,
+            num_witness,
+            row_vars,
+            col_vars,
+        }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    pub fn num_witness(&self) -> usize {
+        self.num_witness
+    }
+
+    // the padded witness half of z, the table the witness claim is about
+    pub fn witness_table(&self, witness: &[F]) -> Vec<F> {
+        if witness.len() != self.num_witness {
+            panic!("wrong number of witness values");
+        }
+
+        let mut table = witness.to_vec();
+        table.resize(1 << (self.col_vars - 1), F::zero());
+        table
+    }
+
+    // the padded (1, inputs) half of z
+    pub fn input_table(&self, inputs: &[F]) -> Vec<F> {
+        if inputs.len() != self.num_inputs {
+            panic!("wrong number of public inputs");
+        }
+
+        let mut table = [&[F::one()], inputs].concat();
+        table.resize(1 << (self.col_vars - 1), F::zero());
+        table
+    }
+
+    pub fn z(&self, inputs: &[F], witness: &[F]) -> Vec<F> {
+        [self.witness_table(witness), self.input_table(inputs)].concat()
+    }
+
+    // M z for one of the matrices, padded to 2^row_vars rows
+    pub(crate) fn multiply(&self, matrix: &SparseMultilinearPoly<F>, z: &[F]) -> Vec<F> {
+        let mut result = vec![F::zero(); 1 << self.row_vars];
+        let col_mask = (1 << self.col_vars) - 1;
+
+        for (index, value) in matrix.evals.iter() {
+            result[index >> self.col_vars] += *value * z[index & col_mask];
+        }
+
+        result
+    }
+
+    pub fn is_satisfied(&self, inputs: &[F], witness: &[F]) -> bool {
+        let z = self.z(inputs, witness);
+        let az = self.multiply(&self.a, &z);
+        let bz = self.multiply(&self.b, &z);
+        let cz = self.multiply(&self.c, &z);
+
+        (0..self.num_constraints).all(|i| az[i] * bz[i] == cz[i])
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{Variable, R1CS};
+    use ark_bn254::Fr;
+
+    // x^3 + x + 5 = out, with out public and x, x^2, x^3 in the witness
+    pub(crate) fn cubic() -> R1CS<Fr> {
+        let one = Fr::from(1);
+        let (x, x2, x3) = (
+            Variable::Witness(0),
+            Variable::Witness(1),
+            Variable::Witness(2),
+        );
+
+        R1CS::new(
+            3,
+            1,
+            3,
+            vec![
+                (0, x, one),
+                (1, x2, one),
+                (2, x3, one),
+                (2, x, one),
+                (2, Variable::One, Fr::from(5)),
+            ],
+            vec![(0, x, one), (1, x, one), (2, Variable::One, one)],
+            vec![(0, x2, one), (1, x3, one), (2, Variable::Input(0), one)],
+        )
+    }
+
+    #[test]
+    fn it_checks_satisfiability() {
+        let r1cs = cubic();
+        let witness = [Fr::from(3), Fr::from(9), Fr::from(27)];
+
+        assert!(r1cs.is_satisfied(&[Fr::from(35)], &witness));
+        assert!(!r1cs.is_satisfied(&[Fr::from(36)], &witness));
+        assert!(!r1cs.is_satisfied(&[Fr::from(35)], &[Fr::from(3), Fr::from(9), Fr::from(26)]));
+    }
+
+    #[test]
+    fn z_puts_the_witness_first() {
+        let r1cs = cubic();
+        let z = r1cs.z(&[Fr::from(35)], &[Fr::from(3), Fr::from(9), Fr::from(27)]);
+
+        assert_eq!(r1cs.row_vars, 2);
+        assert_eq!(r1cs.col_vars, 3);
+        assert_eq!(z, [3, 9, 27, 0, 1, 35, 0, 0].map(Fr::from).to_vec());
+    }
+
+    #[test]
+    #[should_panic(expected = "entry refers to a variable that does not exist")]
+    fn it_rejects_unknown_variables() {
+        R1CS::<Fr>::new(
+            1,
+            1,
+            1,
+            vec![(0, Variable::Input(1), Fr::from(1))],
+            vec![],
+            vec![],
+        );
+    }
+}
@@ -58,6 +58,7 @@ use rand::{Rng, SeedableRng};
 //         points.append((i, accum))
 
 //     return points
+#[allow(dead_code)]
 fn create_share(polynomial: Vec<Fq>, threshold: usize, num_of_points: usize) -> Vec<(Fq, Fq)> {
     if threshold > num_of_points {
         panic!("Threshold should not be greater than x_points");
@@ -77,19 +78,14 @@ fn create_share(polynomial: Vec<Fq>, threshold: usize, num_of_points: usize) ->
     shares
 }
 
+#[allow(dead_code)]
 fn get_secret(poly: &UnivariatePolynomial<Fq>, x_point: Fq) -> Fq {
-    let secret = poly.evaluate(Fq::from(x_point));
-
-    secret
+    poly.evaluate(x_point)
 }
 
 #[cfg(test)]
-
 mod test {
-    use crate::libs::{
-        polynomial::{self, UnivariatePolynomial},
-        shamir_secret_sharing::get_secret,
-    };
+    use crate::libs::{polynomial::UnivariatePolynomial, shamir_secret_sharing::get_secret};
     use ark_bn254::Fq;
 
     use super::create_share;
@@ -0,0 +1,302 @@
+// malicious provers built by mutating honest proofs, and completeness over random
+// instances. every mutation has to be caught, either by the verifier itself or, for a bare
+// sumcheck, by the final oracle check it hands back
+use super::fiat_shamir::Transcript;
+use super::gkr_circuits_impl::{Circuit, Operation};
+use super::gkr_protocol::{prove, verify, Proof};
+use super::multilinear_poly::MultilinearPoly;
+use super::sum_check_protocol::{
+    CompressedSumcheckProof, SumcheckProof, SumcheckProver, SumcheckVerifier,
+};
+use super::virtual_poly::VirtualPoly;
+use ark_bn254::Fq;
+use ark_std::UniformRand;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// a layered circuit with a random number of outputs, every layer below doubling in width
+fn random_circuit(rng: &mut StdRng) -> (Circuit<Fq>, Vec<Fq>) {
+    let depth = rng.gen_range(1..=4);
+    let width = rng.gen_range(1..=3);
+
+    let structure = (0..depth)
+        .rev()
+        .map(|layer| {
+            (0..width << layer)
+                .map(|_| {
+                    if rng.gen_bool(0.5) {
+                        Operation::Add
+                    } else {
+                        Operation::Mul
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let inputs = (0..width << depth).map(|_| Fq::rand(rng)).collect();
+
+    (Circuit::new(structure), inputs)
+}
+
+fn random_poly(rng: &mut StdRng) -> VirtualPoly<Fq> {
+    let n_vars = rng.gen_range(1..=4);
+    let mut poly = VirtualPoly::new(n_vars);
+
+    let tables: Vec<usize> = (0..rng.gen_range(1..=3))
+        .map(|_| {
+            let evals = (0..1 << n_vars).map(|_| Fq::rand(rng)).collect();
+            poly.add_table(MultilinearPoly::new(evals))
+        })
+        .collect();
+
+    for _ in 0..rng.gen_range(1..=3) {
+        let factors = (0..rng.gen_range(1..=3))
+            .map(|_| tables[rng.gen_range(0..tables.len())])
+            .collect();
+        poly.add_term(Fq::rand(rng), factors);
+    }
+
+    poly
+}
+
+// every proof that differs from the honest one in exactly one place
+fn gkr_mutations(proof: &Proof<Fq>) -> Vec<(String, Proof<Fq>)> {
+    let mut mutations = vec![];
+    let mut push = |name: String, mutate: &dyn Fn(&mut Proof<Fq>)| {
+        let mut mutated = proof.clone();
+        mutate(&mut mutated);
+        mutations.push((name, mutated));
+    };
+
+    for (layer, sumcheck_proof) in proof.sumcheck_proofs.iter().enumerate() {
+        push(format!("layer {} claimed sum", layer), &|p| {
+            p.sumcheck_proofs[layer].claimed_sum += Fq::from(1)
+        });
+
+        for (round, round_poly) in sumcheck_proof.round_polys.iter().enumerate() {
+            for k in 0..round_poly.len() {
+                push(
+                    format!("layer {} round {} eval {}", layer, round, k),
+                    &|p| p.sumcheck_proofs[layer].round_polys[round][k] += Fq::from(1),
+                );
+            }
+
+            push(format!("layer {} without round {}", layer, round), &|p| {
+                p.sumcheck_proofs[layer].round_polys.remove(round);
+            });
+            push(format!("layer {} round {} repeated", layer, round), &|p| {
+                let round_poly = p.sumcheck_proofs[layer].round_polys[round].clone();
+                p.sumcheck_proofs[layer]
+                    .round_polys
+                    .insert(round, round_poly);
+            });
+        }
+    }
+
+    for layer in 0..proof.claimed_evaluations.len() {
+        push(format!("layer {} o_1", layer), &|p| {
+            p.claimed_evaluations[layer].0 += Fq::from(1)
+        });
+        push(format!("layer {} o_2", layer), &|p| {
+            p.claimed_evaluations[layer].1 += Fq::from(1)
+        });
+        push(format!("layer {} o_1 and o_2 swapped", layer), &|p| {
+            let (o_1, o_2) = p.claimed_evaluations[layer];
+            p.claimed_evaluations[layer] = (o_2, o_1);
+        });
+    }
+
+    for layer in 1..proof.sumcheck_proofs.len() {
+        push(
+            format!("layers {} and {} swapped", layer - 1, layer),
+            &|p| {
+                p.sumcheck_proofs.swap(layer - 1, layer);
+                if layer < p.claimed_evaluations.len() {
+                    p.claimed_evaluations.swap(layer - 1, layer);
+                }
+            },
+        );
+    }
+
+    push("last layer dropped".to_string(), &|p| {
+        p.sumcheck_proofs.pop();
+        p.claimed_evaluations.pop();
+    });
+
+    mutations
+}
+
+// works on the claimed sum and rounds, so full and compressed proofs share it. a compressed
+// proof is mutated as sent, mutating p(1) before compressing would be a no op
+fn sumcheck_mutations(claimed_sum: Fq, round_polys: &[Vec<Fq>]) -> Vec<(String, Fq, Vec<Vec<Fq>>)> {
+    let mut mutations = vec![];
+    let mut push = |name: String, mutate: &dyn Fn(&mut Fq, &mut Vec<Vec<Fq>>)| {
+        let (mut sum, mut rounds) = (claimed_sum, round_polys.to_vec());
+        mutate(&mut sum, &mut rounds);
+        mutations.push((name, sum, rounds));
+    };
+
+    push("claimed sum".to_string(), &|sum, _| *sum += Fq::from(1));
+
+    for (round, round_poly) in round_polys.iter().enumerate() {
+        for k in 0..round_poly.len() {
+            push(format!("round {} eval {}", round, k), &|_, rounds| {
+                rounds[round][k] += Fq::from(1)
+            });
+        }
+
+        push(format!("without round {}", round), &|_, rounds| {
+            rounds.remove(round);
+        });
+    }
+
+    for round in 1..round_polys.len() {
+        push(
+            format!("rounds {} and {} swapped", round - 1, round),
+            &|_, rounds| rounds.swap(round - 1, round),
+        );
+    }
+
+    mutations
+}
+
+#[test]
+fn gkr_accepts_random_circuits() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..20 {
+        let (circuit, inputs) = random_circuit(&mut rng);
+
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+        assert_eq!(outputs, circuit.evaluations(&inputs).pop().unwrap());
+        assert_eq!(verify(&proof, &circuit, &inputs, &outputs), Ok(()));
+    }
+}
+
+#[test]
+fn gkr_rejects_every_mutation() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for _ in 0..5 {
+        let (circuit, inputs) = random_circuit(&mut rng);
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+        for (name, mutated) in gkr_mutations(&proof) {
+            assert!(
+                verify(&mutated, &circuit, &inputs, &outputs).is_err(),
+                "accepted a proof with {} changed",
+                name
+            );
+        }
+    }
+}
+
+#[test]
+fn gkr_rejects_other_inputs_or_outputs() {
+    let mut rng = StdRng::seed_from_u64(2);
+
+    for _ in 0..5 {
+        let (circuit, inputs) = random_circuit(&mut rng);
+        let (outputs, proof) = prove(&circuit, &inputs).unwrap();
+
+        let mut other = inputs.clone();
+        let i = rng.gen_range(0..other.len());
+        other[i] += Fq::from(1);
+
+        assert!(verify(&proof, &circuit, &other, &outputs).is_err());
+
+        let mut other_outputs = outputs.clone();
+        let i = rng.gen_range(0..other_outputs.len());
+        other_outputs[i] += Fq::from(1);
+
+        assert!(verify(&proof, &circuit, &inputs, &other_outputs).is_err());
+    }
+}
+
+#[test]
+fn sumcheck_accepts_random_polys() {
+    let mut rng = StdRng::seed_from_u64(3);
+
+    for _ in 0..20 {
+        let poly = random_poly(&mut rng);
+        let claimed_sum = poly.sum_over_hypercube();
+
+        let (proof, prover_claim) =
+            SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+
+        for claim in [
+            SumcheckVerifier::verify(
+                &proof,
+                poly.n_vars,
+                poly.get_degree(),
+                &mut Transcript::new(),
+            ),
+            SumcheckVerifier::verify_compressed(
+                &proof.compress(),
+                poly.n_vars,
+                poly.get_degree(),
+                &mut Transcript::new(),
+            ),
+        ] {
+            let claim = claim.unwrap();
+
+            assert_eq!(claim, prover_claim);
+            assert!(claim.check(|point| poly.evaluate(point)));
+        }
+    }
+}
+
+#[test]
+fn sumcheck_rejects_every_mutation() {
+    let mut rng = StdRng::seed_from_u64(4);
+
+    for _ in 0..5 {
+        let poly = random_poly(&mut rng);
+        let (proof, _) =
+            SumcheckProver::prove(&poly, poly.sum_over_hypercube(), &mut Transcript::new());
+
+        let compressed = proof.compress();
+        let (n_vars, degree) = (poly.n_vars, poly.get_degree());
+
+        let full_results = sumcheck_mutations(proof.claimed_sum, &proof.round_polys)
+            .into_iter()
+            .map(|(name, claimed_sum, round_polys)| {
+                let mutated = SumcheckProof {
+                    claimed_sum,
+                    round_polys,
+                };
+                let result =
+                    SumcheckVerifier::verify(&mutated, n_vars, degree, &mut Transcript::new());
+
+                (name, result)
+            });
+
+        let compressed_results =
+            sumcheck_mutations(compressed.claimed_sum, &compressed.round_polys)
+                .into_iter()
+                .map(|(name, claimed_sum, round_polys)| {
+                    let mutated = CompressedSumcheckProof {
+                        claimed_sum,
+                        round_polys,
+                    };
+                    let result = SumcheckVerifier::verify_compressed(
+                        &mutated,
+                        n_vars,
+                        degree,
+                        &mut Transcript::new(),
+                    );
+
+                    (format!("compressed {}", name), result)
+                });
+
+        for (name, result) in full_results.chain(compressed_results) {
+            assert!(
+                result.map_or(true, |claim| !claim.check(|point| poly.evaluate(point))),
+                "accepted a sumcheck with {} changed",
+                name
+            );
+        }
+    }
+}
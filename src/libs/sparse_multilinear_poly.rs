@@ -0,0 +1,220 @@
+use super::multilinear_poly::MultilinearPoly;
+use ark_ff::PrimeField;
+use std::collections::BTreeMap;
+
+// only the non-zero points of the hypercube are stored, sorted by index.
+// indexing follows MultilinearPoly: variable 0 is the most significant bit
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseMultilinearPoly<F: PrimeField> {
+    pub(crate) evals: Vec<(usize, F)>,
+    pub(crate) n_vars: usize,
+}
+
+impl<F: PrimeField> SparseMultilinearPoly<F> {
+    pub fn new(n_vars: usize, entries: Vec<(usize, F)>) -> Self {
+        let mut merged = BTreeMap::new();
+
+        for (index, value) in entries {
+            if index >= 1 << n_vars {
+                panic!(
+                    "index {} is outside the {}-variable hypercube",
+                    index, n_vars
+                );
+            }
+
+            *merged.entry(index).or_insert(F::zero()) += value;
+        }
+
+        let evals = merged
+            .into_iter()
+            .filter(|(_, value)| !value.is_zero())
+            .collect();
+
+        Self { evals, n_vars }
+    }
+
+    pub fn from_dense(poly: &MultilinearPoly<F>) -> Self {
+        let evals = poly
+            .evals
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(index, value)| (index, *value))
+            .collect();
+
+        Self {
+            evals,
+            n_vars: poly.n_vars,
+        }
+    }
+
+    pub fn to_dense(&self) -> MultilinearPoly<F> {
+        let mut evals = vec![F::zero(); 1 << self.n_vars];
+
+        for (index, value) in self.evals.iter() {
+            evals[*index] = *value;
+        }
+
+        MultilinearPoly::new(evals)
+    }
+
+    pub fn num_non_zero(&self) -> usize {
+        self.evals.len()
+    }
+
+    // O(nnz * n): every stored point contributes value * eq(point, values)
+    pub fn evaluate(&self, values: &[F]) -> F {
+        if values.len() != self.n_vars {
+            panic!("Invalid number of values");
+        }
+
+        self.evals
+            .iter()
+            .map(|(index, value)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(var, r)| {
+                        if bit_at(*index, var, self.n_vars) {
+                            *r
+                        } else {
+                            F::one() - r
+                        }
+                    })
+                    .product::<F>()
+                    * value
+            })
+            .sum()
+    }
+
+    pub fn partial_evaluate(&self, index: usize, value: &F) -> Self {
+        if index >= self.n_vars {
+            panic!("Invalid variable index");
+        }
+
+        let one_minus_value = F::one() - value;
+
+        let entries = self
+            .evals
+            .iter()
+            .map(|(point, eval)| {
+                let weight = if bit_at(*point, index, self.n_vars) {
+                    *value
+                } else {
+                    one_minus_value
+                };
+
+                (remove_bit(*point, self.n_vars - index - 1), *eval * weight)
+            })
+            .collect();
+
+        Self::new(self.n_vars - 1, entries)
+    }
+
+    pub fn multi_partial_evaluate(&self, values: &[F]) -> Self {
+        if values.len() > self.n_vars {
+            panic!("Invalid number of values");
+        }
+
+        let mut poly = self.clone();
+
+        for value in values.iter() {
+            poly = poly.partial_evaluate(0, value);
+        }
+
+        poly
+    }
+
+    pub fn scale(&self, value: F) -> Self {
+        let entries = self
+            .evals
+            .iter()
+            .map(|(index, eval)| (*index, *eval * value))
+            .collect();
+
+        Self::new(self.n_vars, entries)
+    }
+}
+
+fn bit_at(point: usize, var: usize, n_vars: usize) -> bool {
+    (point >> (n_vars - var - 1)) & 1 == 1
+}
+
+// inverse of multilinear_poly::insert_bit
+// 101 remove at 1 -> 11
+fn remove_bit(value: usize, index: usize) -> usize {
+    let high = value >> (index + 1);
+    let low = value & ((1 << index) - 1);
+
+    (high << index) | low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remove_bit, SparseMultilinearPoly};
+    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use ark_bn254::Fr;
+
+    // 2ab + 3bc
+    fn dense() -> MultilinearPoly<Fr> {
+        MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]))
+    }
+
+    #[test]
+    fn bit_removal() {
+        assert_eq!(remove_bit(0b110, 0), 0b11);
+        assert_eq!(remove_bit(0b101, 1), 0b11);
+        assert_eq!(remove_bit(0b011, 2), 0b11);
+    }
+
+    #[test]
+    fn it_converts_to_and_from_dense() {
+        let sparse = SparseMultilinearPoly::from_dense(&dense());
+
+        assert_eq!(sparse.num_non_zero(), 3);
+        assert_eq!(sparse.to_dense(), dense());
+    }
+
+    #[test]
+    fn it_merges_duplicate_entries() {
+        let sparse = SparseMultilinearPoly::new(
+            2,
+            vec![
+                (3, Fr::from(1)),
+                (1, Fr::from(4)),
+                (3, Fr::from(2)),
+                (2, Fr::from(0)),
+            ],
+        );
+
+        assert_eq!(sparse.evals, vec![(1, Fr::from(4)), (3, Fr::from(3))]);
+    }
+
+    #[test]
+    fn it_evaluates_like_dense() {
+        let sparse = SparseMultilinearPoly::from_dense(&dense());
+        let values = to_field(vec![2, 3, 4]);
+
+        assert_eq!(sparse.evaluate(&values), dense().evaluate(values));
+    }
+
+    #[test]
+    fn it_partially_evaluates_like_dense() {
+        let sparse = SparseMultilinearPoly::from_dense(&dense());
+
+        for index in 0..3 {
+            assert_eq!(
+                sparse.partial_evaluate(index, &Fr::from(3)).to_dense(),
+                dense().partial_evaluate(index, &Fr::from(3))
+            );
+        }
+
+        assert_eq!(
+            sparse
+                .multi_partial_evaluate(&to_field(vec![5, 7]))
+                .to_dense(),
+            dense().multi_partial_evaluate(&to_field(vec![5, 7]))
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use super::fiat_shamir::{fq_vec_to_bytes, Transcript};
+use super::multilinear_poly::{eq_eval, MultilinearPoly};
+use super::r1cs::R1CS;
+use super::sum_check_protocol::{
+    CompressedSumcheckProof, EvaluationClaim, SumcheckError, SumcheckProver, SumcheckVerifier,
+};
+use super::virtual_poly::VirtualPoly;
+use ark_ff::PrimeField;
+use std::fmt;
+
+// z satisfies the R1CS iff 0 = sum_x eq(tau, x) * (Az(x) * Bz(x) - Cz(x)) for a random tau.
+// the outer sumcheck reduces that to Az, Bz and Cz at r_x, a random combination of those
+// is sum_y (r_a * A(r_x, y) + r_b * B(r_x, y) + r_c * C(r_x, y)) * z(y), and the inner
+// sumcheck reduces it to the matrices and z at r_y. the verifier evaluates the sparse
+// matrices itself, and z from the inputs plus the claimed witness evaluation
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpartanProof<F: PrimeField> {
+    pub outer: CompressedSumcheckProof<F>,
+    // Az(r_x), Bz(r_x), Cz(r_x)
+    pub matrix_evaluations: (F, F, F),
+    pub inner: CompressedSumcheckProof<F>,
+    // w(r_y without its first coordinate)
+    pub witness_evaluation: F,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpartanError {
+    WrongNumberOfInputs { expected: usize, got: usize },
+    WrongNumberOfWitnessValues { expected: usize, got: usize },
+    OuterClaimMismatch,
+    Outer(SumcheckError),
+    OuterEvaluationMismatch,
+    InnerClaimMismatch,
+    Inner(SumcheckError),
+    InnerEvaluationMismatch,
+}
+
+impl fmt::Display for SpartanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpartanError::WrongNumberOfInputs { expected, got } => {
+                write!(f, "expected {} public inputs, got {}", expected, got)
+            }
+            SpartanError::WrongNumberOfWitnessValues { expected, got } => {
+                write!(f, "expected {} witness values, got {}", expected, got)
+            }
+            SpartanError::OuterClaimMismatch => write!(f, "outer sumcheck does not claim zero"),
+            SpartanError::Outer(error) => write!(f, "outer sumcheck: {}", error),
+            SpartanError::OuterEvaluationMismatch => {
+                write!(f, "matrix evaluations do not satisfy the constraints")
+            }
+            SpartanError::InnerClaimMismatch => {
+                write!(
+                    f,
+                    "inner sumcheck does not start from the matrix evaluations"
+                )
+            }
+            SpartanError::Inner(error) => write!(f, "inner sumcheck: {}", error),
+            SpartanError::InnerEvaluationMismatch => {
+                write!(f, "inner sumcheck does not end at the matrices and z")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpartanError {}
+
+// the matrices at r_x, combined with the challenges: sum over rows of eq(r_x, row) * value
+fn combined_rows<F: PrimeField>(r1cs: &R1CS<F>, r_x: &[F], challenges: [F; 3]) -> Vec<F> {
+    let eq = MultilinearPoly::eq(r_x);
+    let col_mask = (1 << r1cs.col_vars) - 1;
+    let mut rows = vec![F::zero(); 1 << r1cs.col_vars];
+
+    for (matrix, challenge) in [&r1cs.a, &r1cs.b, &r1cs.c].iter().zip(challenges) {
+        for (index, value) in matrix.evals.iter() {
+            rows[index & col_mask] += challenge * eq.evals[index >> r1cs.col_vars] * value;
+        }
+    }
+
+    rows
+}
+
+fn matrix_challenges<F: PrimeField>(
+    (a, b, c): (F, F, F),
+    transcript: &mut Transcript<F>,
+) -> [F; 3] {
+    transcript.append(&fq_vec_to_bytes(&[a, b, c]));
+
+    [(); 3].map(|_| transcript.get_random_challenge())
+}
+
+// the transcript should already bind the witness. the returned claim is on the padded
+// witness table, R1CS::witness_table. inputs and witness are checked against the R1CS
+// before anything is absorbed, like verify does for the inputs
+pub fn prove<F: PrimeField>(
+    r1cs: &R1CS<F>,
+    inputs: &[F],
+    witness: &[F],
+    transcript: &mut Transcript<F>,
+) -> Result<(SpartanProof<F>, EvaluationClaim<F>), SpartanError> {
+    if inputs.len() != r1cs.num_inputs() {
+        return Err(SpartanError::WrongNumberOfInputs {
+            expected: r1cs.num_inputs(),
+            got: inputs.len(),
+        });
+    }
+
+    if witness.len() != r1cs.num_witness() {
+        return Err(SpartanError::WrongNumberOfWitnessValues {
+            expected: r1cs.num_witness(),
+            got: witness.len(),
+        });
+    }
+
+    let z = r1cs.z(inputs, witness);
+
+    transcript.append(&fq_vec_to_bytes(inputs));
+    let tau: Vec<F> = (0..r1cs.row_vars)
+        .map(|_| transcript.get_random_challenge())
+        .collect();
+
+    let mut outer_poly = VirtualPoly::new(r1cs.row_vars);
+    let eq = outer_poly.add_table(MultilinearPoly::eq(&tau));
+    let [az, bz, cz] = [&r1cs.a, &r1cs.b, &r1cs.c]
+        .map(|matrix| outer_poly.add_table(MultilinearPoly::new(r1cs.multiply(matrix, &z))));
+    outer_poly.add_term(F::one(), vec![eq, az, bz]);
+    outer_poly.add_term(-F::one(), vec![eq, cz]);
+
+    let (outer, r_x, folded) = SumcheckProver::prove_rounds(&outer_poly, F::zero(), transcript);
+    let [a, b, c] = [az, bz, cz].map(|table| folded.tables[table].evals[0]);
+
+    let challenges = matrix_challenges((a, b, c), transcript);
+    let [r_a, r_b, r_c] = challenges;
+
+    let mut inner_poly = VirtualPoly::new(r1cs.col_vars);
+    let rows = inner_poly.add_table(MultilinearPoly::new(combined_rows(r1cs, &r_x, challenges)));
+    let z_table = inner_poly.add_table(MultilinearPoly::new(z));
+    inner_poly.add_term(F::one(), vec![rows, z_table]);
+
+    let (inner, r_y, _) =
+        SumcheckProver::prove_rounds(&inner_poly, r_a * a + r_b * b + r_c * c, transcript);
+
+    let witness_point = r_y[1..].to_vec();
+    let witness_evaluation =
+        MultilinearPoly::new(r1cs.witness_table(witness)).evaluate(witness_point.clone());
+
+    transcript.append(&fq_vec_to_bytes(&[witness_evaluation]));
+
+    let proof = SpartanProof {
+        outer: outer.compress(),
+        matrix_evaluations: (a, b, c),
+        inner: inner.compress(),
+        witness_evaluation,
+    };
+
+    let claim = EvaluationClaim {
+        point: witness_point,
+        value: witness_evaluation,
+    };
+
+    Ok((proof, claim))
+}
+
+pub fn verify<F: PrimeField>(
+    r1cs: &R1CS<F>,
+    inputs: &[F],
+    proof: &SpartanProof<F>,
+    transcript: &mut Transcript<F>,
+) -> Result<EvaluationClaim<F>, SpartanError> {
+    if inputs.len() != r1cs.num_inputs {
+        return Err(SpartanError::WrongNumberOfInputs {
+            expected: r1cs.num_inputs,
+            got: inputs.len(),
+        });
+    }
+
+    transcript.append(&fq_vec_to_bytes(inputs));
+    let tau: Vec<F> = (0..r1cs.row_vars)
+        .map(|_| transcript.get_random_challenge())
+        .collect();
+
+    if !proof.outer.claimed_sum.is_zero() {
+        return Err(SpartanError::OuterClaimMismatch);
+    }
+
+    let outer_claim =
+        SumcheckVerifier::verify_compressed(&proof.outer, r1cs.row_vars, 3, transcript)
+            .map_err(SpartanError::Outer)?;
+
+    let (a, b, c) = proof.matrix_evaluations;
+    if eq_eval(&tau, &outer_claim.point) * (a * b - c) != outer_claim.value {
+        return Err(SpartanError::OuterEvaluationMismatch);
+    }
+
+    let [r_a, r_b, r_c] = matrix_challenges((a, b, c), transcript);
+
+    if proof.inner.claimed_sum != r_a * a + r_b * b + r_c * c {
+        return Err(SpartanError::InnerClaimMismatch);
+    }
+
+    let inner_claim =
+        SumcheckVerifier::verify_compressed(&proof.inner, r1cs.col_vars, 2, transcript)
+            .map_err(SpartanError::Inner)?;
+
+    let r_y = &inner_claim.point;
+    let point = [outer_claim.point.as_slice(), r_y].concat();
+    let rows = r_a * r1cs.a.evaluate(&point)
+        + r_b * r1cs.b.evaluate(&point)
+        + r_c * r1cs.c.evaluate(&point);
+
+    let inputs_at = MultilinearPoly::new(r1cs.input_table(inputs)).evaluate(r_y[1..].to_vec());
+    let z_at = (F::one() - r_y[0]) * proof.witness_evaluation + r_y[0] * inputs_at;
+
+    if rows * z_at != inner_claim.value {
+        return Err(SpartanError::InnerEvaluationMismatch);
+    }
+
+    transcript.append(&fq_vec_to_bytes(&[proof.witness_evaluation]));
+
+    Ok(EvaluationClaim {
+        point: r_y[1..].to_vec(),
+        value: proof.witness_evaluation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prove, verify, SpartanError};
+    use crate::libs::multilinear_poly::tests::committed_transcript;
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use crate::libs::r1cs::tests::cubic;
+    use crate::libs::sum_check_protocol::SumcheckError;
+    use ark_bn254::Fr;
+
+    const COMMITMENT: &[u8] = b"commitment to the witness";
+
+    #[test]
+    fn it_proves_a_satisfied_r1cs() {
+        let r1cs = cubic();
+        let inputs = [Fr::from(35)];
+        let witness = [Fr::from(3), Fr::from(9), Fr::from(27)];
+
+        let (proof, prover_claim) = prove(
+            &r1cs,
+            &inputs,
+            &witness,
+            &mut committed_transcript(COMMITMENT),
+        )
+        .unwrap();
+        let claim = verify(
+            &r1cs,
+            &inputs,
+            &proof,
+            &mut committed_transcript(COMMITMENT),
+        )
+        .unwrap();
+
+        assert_eq!(claim, prover_claim);
+
+        // no commitment, so the test plays the witness oracle
+        let witness_poly = MultilinearPoly::new(r1cs.witness_table(&witness));
+        assert!(claim.check(|point| witness_poly.evaluate(point.to_vec())));
+    }
+
+    #[test]
+    fn it_rejects_an_unsatisfied_r1cs() {
+        let r1cs = cubic();
+        let witness = [Fr::from(3), Fr::from(9), Fr::from(26)];
+
+        // compressed rounds rebuild p(1) from the zero claim, so the bad sum shows at the end
+        let (proof, _) = prove(
+            &r1cs,
+            &[Fr::from(35)],
+            &witness,
+            &mut committed_transcript(COMMITMENT),
+        )
+        .unwrap();
+        assert_eq!(
+            verify(
+                &r1cs,
+                &[Fr::from(35)],
+                &proof,
+                &mut committed_transcript(COMMITMENT)
+            ),
+            Err(SpartanError::OuterEvaluationMismatch)
+        );
+
+        let mut truncated = proof.clone();
+        truncated.outer.round_polys.pop();
+        assert_eq!(
+            verify(
+                &r1cs,
+                &[Fr::from(35)],
+                &truncated,
+                &mut committed_transcript(COMMITMENT)
+            ),
+            Err(SpartanError::Outer(SumcheckError::WrongNumberOfRounds {
+                expected: 2,
+                got: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn it_rejects_other_inputs_or_witness() {
+        let r1cs = cubic();
+        let inputs = [Fr::from(35)];
+        let witness = [Fr::from(3), Fr::from(9), Fr::from(27)];
+
+        let (proof, _) = prove(
+            &r1cs,
+            &inputs,
+            &witness,
+            &mut committed_transcript(COMMITMENT),
+        )
+        .unwrap();
+
+        // the inputs are absorbed first, so every challenge moves
+        assert!(verify(
+            &r1cs,
+            &[Fr::from(36)],
+            &proof,
+            &mut committed_transcript(COMMITMENT)
+        )
+        .is_err());
+
+        let mut wrong_witness = proof.clone();
+        wrong_witness.witness_evaluation += Fr::from(1);
+        assert_eq!(
+            verify(
+                &r1cs,
+                &inputs,
+                &wrong_witness,
+                &mut committed_transcript(COMMITMENT)
+            ),
+            Err(SpartanError::InnerEvaluationMismatch)
+        );
+
+        assert_eq!(
+            verify(&r1cs, &[], &proof, &mut committed_transcript(COMMITMENT)),
+            Err(SpartanError::WrongNumberOfInputs {
+                expected: 1,
+                got: 0
+            })
+        );
+    }
+
+    #[test]
+    fn it_rejects_inputs_or_witness_of_the_wrong_length() {
+        let r1cs = cubic();
+        let witness = [Fr::from(3), Fr::from(9), Fr::from(27)];
+
+        assert_eq!(
+            prove(
+                &r1cs,
+                &[Fr::from(35), Fr::from(1)],
+                &witness,
+                &mut committed_transcript(COMMITMENT)
+            ),
+            Err(SpartanError::WrongNumberOfInputs {
+                expected: 1,
+                got: 2
+            })
+        );
+        assert_eq!(
+            prove(
+                &r1cs,
+                &[Fr::from(35)],
+                &witness[..2],
+                &mut committed_transcript(COMMITMENT)
+            ),
+            Err(SpartanError::WrongNumberOfWitnessValues {
+                expected: 3,
+                got: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use super::fiat_shamir::{fq_vec_to_bytes, Transcript};
+use super::multilinear_poly::MultilinearPoly;
+use super::sum_check_protocol::{
+    table_round_evaluations, EvaluationClaim, SumcheckPoly, SumcheckProof,
+};
+use super::virtual_poly::VirtualPoly;
+use ark_ff::PrimeField;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+
+pub type Evaluations<'a, F> = Box<dyn Iterator<Item = io::Result<F>> + 'a>;
+
+// the evaluations of a multilinear poly in MsbFirst order, which can be read more than once
+pub trait EvaluationSource<F: PrimeField> {
+    fn n_vars(&self) -> usize;
+    fn pass(&self) -> io::Result<Evaluations<'_, F>>;
+}
+
+pub struct VecSource<F: PrimeField> {
+    evals: Vec<F>,
+}
+
+impl<F: PrimeField> VecSource<F> {
+    pub fn new(evals: Vec<F>) -> Self {
+        if !evals.len().is_power_of_two() {
+            panic!("number of evaluations must be a power of two");
+        }
+
+        Self { evals }
+    }
+}
+
+impl<F: PrimeField> EvaluationSource<F> for VecSource<F> {
+    fn n_vars(&self) -> usize {
+        self.evals.len().ilog2() as usize
+    }
+
+    fn pass(&self) -> io::Result<Evaluations<'_, F>> {
+        Ok(Box::new(self.evals.iter().map(|eval| Ok(*eval))))
+    }
+}
+
+// uncompressed field elements back to back, as written by write_evaluations
+pub struct FileSource {
+    path: PathBuf,
+    n_vars: usize,
+}
+
+impl FileSource {
+    pub fn new(path: PathBuf, n_vars: usize) -> Self {
+        Self { path, n_vars }
+    }
+}
+
+impl<F: PrimeField> EvaluationSource<F> for FileSource {
+    fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    fn pass(&self) -> io::Result<Evaluations<'_, F>> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+
+        Ok(Box::new((0..1usize << self.n_vars).map(move |_| {
+            F::deserialize_uncompressed(&mut reader as &mut dyn Read)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+        })))
+    }
+}
+
+pub fn write_evaluations<F: PrimeField, I: IntoIterator<Item = F>>(
+    path: &PathBuf,
+    evals: I,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    for eval in evals {
+        eval.serialize_uncompressed(&mut writer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    }
+
+    writer.flush()
+}
+
+// sum of coefficient * product of sources, like VirtualPoly but with every table behind an
+// EvaluationSource. a pass reads all sources side by side, one tuple per point
+pub struct StreamingPoly<'a, F: PrimeField> {
+    sources: Vec<&'a dyn EvaluationSource<F>>,
+    terms: Vec<(F, Vec<usize>)>,
+    n_vars: usize,
+}
+
+impl<'a, F: PrimeField> StreamingPoly<'a, F> {
+    pub fn new(n_vars: usize) -> Self {
+        Self {
+            sources: vec![],
+            terms: vec![],
+            n_vars,
+        }
+    }
+
+    // returns the index terms use to refer to the source
+    pub fn add_source(&mut self, source: &'a dyn EvaluationSource<F>) -> usize {
+        if source.n_vars() != self.n_vars {
+            panic!("all sources must have the same number of variables");
+        }
+
+        self.sources.push(source);
+
+        self.sources.len() - 1
+    }
+
+    pub fn add_term(&mut self, coefficient: F, source_indices: Vec<usize>) {
+        if source_indices
+            .iter()
+            .any(|index| *index >= self.sources.len())
+        {
+            panic!("term refers to a source that was never added");
+        }
+
+        self.terms.push((coefficient, source_indices));
+    }
+
+    pub fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    pub fn get_degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|(_, indices)| indices.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // calls f with k and the k-th evaluation of every source, for every point in order
+    fn for_each_tuple<G: FnMut(usize, &[F])>(&self, mut f: G) -> io::Result<()> {
+        let mut passes = self
+            .sources
+            .iter()
+            .map(|source| source.pass())
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut tuple = vec![F::zero(); passes.len()];
+
+        for k in 0..1usize << self.n_vars {
+            for (value, pass) in tuple.iter_mut().zip(passes.iter_mut()) {
+                *value = pass.next().unwrap_or_else(|| {
+                    Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "source ended before 2^n_vars evaluations",
+                    ))
+                })?;
+            }
+
+            f(k, &tuple);
+        }
+
+        Ok(())
+    }
+}
+
+// the rounds are the same as SumcheckProver::prove on the VirtualPoly of the whole tables,
+// so the proof verifies with SumcheckVerifier. while the tables with the challenges so far
+// fixed are bigger than memory_budget field elements, rounds are streamed. a round of
+// degree one terms is one pass that keeps O(n) state. products do not distribute over the
+// fixed prefix, so there every pass folds a chunk of memory_budget / (2 * sources) points
+// of the round variable's low half and its high half, and a round takes half / chunk
+// passes. once the tables fit, one more pass builds them and the remaining rounds fold
+// them in memory. a bigger budget buys fewer passes
+pub struct StreamingProver {
+    memory_budget: usize,
+}
+
+impl StreamingProver {
+    pub fn new(memory_budget: usize) -> Self {
+        if memory_budget == 0 {
+            panic!("memory budget must hold at least one field element");
+        }
+
+        Self { memory_budget }
+    }
+
+    pub fn prove<F: PrimeField>(
+        &self,
+        poly: &StreamingPoly<F>,
+        claimed_sum: F,
+        transcript: &mut Transcript<F>,
+    ) -> io::Result<(SumcheckProof<F>, EvaluationClaim<F>)> {
+        let n_vars = poly.n_vars();
+        let n_sources = poly.sources.len();
+        let mut round_polys = Vec::with_capacity(n_vars);
+        let mut challenges = Vec::with_capacity(n_vars);
+
+        transcript.append(&fq_vec_to_bytes(&[claimed_sum]));
+
+        while challenges.len() < n_vars
+            && (n_sources << (n_vars - challenges.len())) > self.memory_budget
+        {
+            let round_poly = self.streamed_round(poly, &challenges)?;
+
+            transcript.append(&fq_vec_to_bytes(&round_poly));
+            round_polys.push(round_poly);
+            challenges.push(transcript.get_random_challenge());
+        }
+
+        let mut fixed = VirtualPoly::new(n_vars - challenges.len());
+        for table in fixed_tables(poly, &challenges)? {
+            fixed.add_table(MultilinearPoly::new(table));
+        }
+        for (coefficient, indices) in poly.terms.iter() {
+            fixed.add_term(*coefficient, indices.clone());
+        }
+
+        while challenges.len() < n_vars {
+            let round_poly = fixed.round_polynomial();
+
+            transcript.append(&fq_vec_to_bytes(&round_poly));
+            round_polys.push(round_poly);
+
+            let challenge = transcript.get_random_challenge();
+            challenges.push(challenge);
+            SumcheckPoly::fold(&mut fixed, &challenge);
+        }
+
+        let proof = SumcheckProof {
+            claimed_sum,
+            round_polys,
+        };
+
+        let claim = EvaluationClaim {
+            point: challenges,
+            value: fixed.sum_over_hypercube(),
+        };
+
+        Ok((proof, claim))
+    }
+
+    fn streamed_round<F: PrimeField>(
+        &self,
+        poly: &StreamingPoly<F>,
+        challenges: &[F],
+    ) -> io::Result<Vec<F>> {
+        let half = 1usize << (poly.n_vars() - challenges.len() - 1);
+
+        if poly.terms.iter().all(|(_, indices)| indices.len() == 1) {
+            return streamed_chunk(poly, challenges, 0..half, true);
+        }
+
+        let chunk = (self.memory_budget / (2 * poly.sources.len())).clamp(1, half);
+        let mut round_poly = vec![F::zero(); poly.get_degree() + 1];
+
+        for start in (0..half).step_by(chunk) {
+            let chunk_poly =
+                streamed_chunk(poly, challenges, start..(start + chunk).min(half), false)?;
+
+            for (sum, eval) in round_poly.iter_mut().zip(chunk_poly.iter()) {
+                *sum += eval;
+            }
+        }
+
+        Ok(round_poly)
+    }
+}
+
+// eq(challenges, prefix) where prefix is read MsbFirst with challenges.len() bits
+fn eq_prefix<F: PrimeField>(challenges: &[F], prefix: usize) -> F {
+    challenges
+        .iter()
+        .enumerate()
+        .map(|(j, r)| {
+            if (prefix >> (challenges.len() - 1 - j)) & 1 == 1 {
+                *r
+            } else {
+                F::one() - r
+            }
+        })
+        .product()
+}
+
+// in MsbFirst order every prefix of fixed variables covers one contiguous block, with the
+// current variable 0 in the first half of it and 1 in the second. every source is folded
+// over the prefixes at the points of chunk in both halves, then the terms are multiplied
+// out as in memory. with collapse the chunk is summed into one point first, which only
+// gives the round poly when every term is linear
+fn streamed_chunk<F: PrimeField>(
+    poly: &StreamingPoly<F>,
+    challenges: &[F],
+    chunk: Range<usize>,
+    collapse: bool,
+) -> io::Result<Vec<F>> {
+    let block = 1usize << (poly.n_vars() - challenges.len());
+    let half = block / 2;
+    let width = if collapse { 1 } else { chunk.len() };
+    let mut tables = vec![vec![F::zero(); 2 * width]; poly.sources.len()];
+    let mut weight = F::zero();
+
+    poly.for_each_tuple(|k, tuple| {
+        if k % block == 0 {
+            weight = eq_prefix(challenges, k / block);
+        }
+
+        let (high, j) = ((k % block) / half, k % half);
+        if !chunk.contains(&j) {
+            return;
+        }
+
+        let index = high * width + if collapse { 0 } else { j - chunk.start };
+        for (table, value) in tables.iter_mut().zip(tuple.iter()) {
+            table[index] += weight * value;
+        }
+    })?;
+
+    let tables: Vec<&[F]> = tables.iter().map(|table| table.as_slice()).collect();
+
+    Ok(table_round_evaluations(
+        &tables,
+        &poly.terms,
+        poly.get_degree(),
+    ))
+}
+
+// every table with the challenges fixed: table[j] = sum over prefixes of eq * f(prefix, j)
+fn fixed_tables<F: PrimeField>(
+    poly: &StreamingPoly<F>,
+    challenges: &[F],
+) -> io::Result<Vec<Vec<F>>> {
+    let block = 1usize << (poly.n_vars() - challenges.len());
+    let mut tables = vec![vec![F::zero(); block]; poly.sources.len()];
+    let mut weight = F::zero();
+
+    poly.for_each_tuple(|k, tuple| {
+        if k % block == 0 {
+            weight = eq_prefix(challenges, k / block);
+        }
+
+        for (table, value) in tables.iter_mut().zip(tuple.iter()) {
+            table[k % block] += weight * value;
+        }
+    })?;
+
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        write_evaluations, EvaluationSource, Evaluations, FileSource, StreamingPoly,
+        StreamingProver, VecSource,
+    };
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::MultilinearPoly;
+    use crate::libs::sum_check_protocol::{SumcheckProver, SumcheckVerifier};
+    use crate::libs::virtual_poly::VirtualPoly;
+    use ark_bn254::Fr;
+    use ark_std::{test_rng, UniformRand};
+    use std::cell::Cell;
+    use std::io;
+
+    struct CountingSource {
+        inner: VecSource<Fr>,
+        passes: Cell<usize>,
+    }
+
+    impl EvaluationSource<Fr> for CountingSource {
+        fn n_vars(&self) -> usize {
+            self.inner.n_vars()
+        }
+
+        fn pass(&self) -> io::Result<Evaluations<'_, Fr>> {
+            self.passes.set(self.passes.get() + 1);
+            self.inner.pass()
+        }
+    }
+
+    fn random_evals(n_vars: usize) -> Vec<Fr> {
+        let mut rng = test_rng();
+        (0..1 << n_vars).map(|_| Fr::rand(&mut rng)).collect()
+    }
+
+    // the source on its own, a degree one poly
+    fn single_source(source: &dyn EvaluationSource<Fr>) -> StreamingPoly<'_, Fr> {
+        let mut poly = StreamingPoly::new(source.n_vars());
+        let index = poly.add_source(source);
+        poly.add_term(Fr::from(1), vec![index]);
+        poly
+    }
+
+    #[test]
+    fn streaming_matches_the_in_memory_prover() {
+        let evals = random_evals(6);
+        let poly = MultilinearPoly::new(evals.clone());
+        let claimed_sum = evals.iter().sum();
+
+        let expected = SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+
+        // 2^6 fits in 64: one pass, then in memory. 1: a pass per round plus the last value
+        for (budget, passes) in [(64, 1), (16, 3), (5, 5), (1, 7)] {
+            let source = CountingSource {
+                inner: VecSource::new(evals.clone()),
+                passes: Cell::new(0),
+            };
+
+            let result = StreamingProver::new(budget)
+                .prove(&single_source(&source), claimed_sum, &mut Transcript::new())
+                .unwrap();
+
+            assert_eq!(result, expected);
+            assert_eq!(source.passes.get(), passes);
+        }
+    }
+
+    #[test]
+    fn it_streams_a_product_of_sources() {
+        let mut rng = test_rng();
+        let tables: Vec<Vec<Fr>> = (0..3)
+            .map(|_| (0..1 << 6).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+
+        // 2 * a * b * c - a * b + 5 * c, degree 3
+        let mut virtual_poly = VirtualPoly::new(6);
+        for table in tables.iter() {
+            virtual_poly.add_table(MultilinearPoly::new(table.clone()));
+        }
+        virtual_poly.add_term(Fr::from(2), vec![0, 1, 2]);
+        virtual_poly.add_term(-Fr::from(1), vec![0, 1]);
+        virtual_poly.add_term(Fr::from(5), vec![2]);
+
+        let claimed_sum = virtual_poly.sum_over_hypercube();
+        let expected = SumcheckProver::prove(&virtual_poly, claimed_sum, &mut Transcript::new());
+
+        // 3 * 2^6 fits in 192. 48 streams the rounds over 64 and 32 points in chunks of 8,
+        // 4 + 2 passes, then builds the tables. 30 has chunks of 5, the last one short, over
+        // 64, 32 and 16 points: 7 + 4 + 2 + 1. 6 goes one point at a time down to 2
+        for (budget, passes) in [(192, 1), (48, 7), (30, 14), (6, 63)] {
+            let sources: Vec<CountingSource> = tables
+                .iter()
+                .map(|table| CountingSource {
+                    inner: VecSource::new(table.clone()),
+                    passes: Cell::new(0),
+                })
+                .collect();
+
+            let mut poly = StreamingPoly::new(6);
+            for source in sources.iter() {
+                poly.add_source(source);
+            }
+            poly.add_term(Fr::from(2), vec![0, 1, 2]);
+            poly.add_term(-Fr::from(1), vec![0, 1]);
+            poly.add_term(Fr::from(5), vec![2]);
+
+            let (proof, claim) = StreamingProver::new(budget)
+                .prove(&poly, claimed_sum, &mut Transcript::new())
+                .unwrap();
+
+            assert_eq!((proof.clone(), claim.clone()), expected);
+            assert!(sources.iter().all(|source| source.passes.get() == passes));
+            assert_eq!(
+                SumcheckVerifier::verify(&proof, 6, 3, &mut Transcript::new()),
+                Ok(claim)
+            );
+        }
+    }
+
+    #[test]
+    fn it_proves_from_a_file() {
+        let evals = random_evals(5);
+        let claimed_sum = evals.iter().sum();
+        let path = std::env::temp_dir().join(format!("sumcheck_evals_{}", std::process::id()));
+
+        write_evaluations(&path, evals.clone()).unwrap();
+
+        let (proof, claim) = StreamingProver::new(4)
+            .prove(
+                &single_source(&FileSource::new(path.clone(), 5)),
+                claimed_sum,
+                &mut Transcript::new(),
+            )
+            .unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        let verified = SumcheckVerifier::verify(&proof, 5, 1, &mut Transcript::new()).unwrap();
+
+        assert_eq!(verified, claim);
+        assert_eq!(
+            claim.value,
+            MultilinearPoly::new(evals).evaluate(claim.point)
+        );
+    }
+
+    #[test]
+    fn it_reports_a_short_file() {
+        let path = std::env::temp_dir().join(format!("sumcheck_short_{}", std::process::id()));
+
+        write_evaluations(&path, random_evals(2)).unwrap();
+
+        let result = StreamingProver::new(1).prove(
+            &single_source(&FileSource::new(path.clone(), 3)),
+            Fr::from(0),
+            &mut Transcript::new(),
+        );
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+}
@@ -1,27 +1,335 @@
 use super::multilinear_poly::{MultilinearPoly, SumPoly};
+use super::virtual_poly::VirtualPoly;
 // use crate::libs::transcript::Transcript;
-use super::fiat_shamir::Transcript;
+use super::fiat_shamir::{field_vec_to_bytes, Transcript};
 // use crate::libs::{multilinear_poly::MultilinearPoly, transcript};
-use super::polynomial::UnivariatePolynomial;
-use ark_bn254::Fq;
-use ark_ff::{BigInteger, PrimeField};
-
-#[derive(Debug)]
-struct Proof<F: PrimeField> {
-    claimed_sum: F,
-    round_polys: Vec<[F; 2]>,
+use super::polynomial::barycentric_evaluate;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fmt;
+
+// round_polys[i] holds the round i polynomial evaluated at 0, 1, ..., degree
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SumcheckProof<F: Field> {
+    pub claimed_sum: F,
+    pub round_polys: Vec<Vec<F>>,
 }
 
-pub struct GkrProof<F: PrimeField> {
-    pub proof_polynomials: Vec<Vec<F>>,
+// same rounds with p(1) left out. the verifier gets it back from p(0) + p(1) = claim,
+// so every round is one field element shorter
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CompressedSumcheckProof<F: PrimeField> {
     pub claimed_sum: F,
-    pub random_challenges: Vec<F>,
+    pub round_polys: Vec<Vec<F>>,
+}
+
+// drops p(1) from one round
+pub(crate) fn compress_round_poly<F: Field>(round_poly: &[F]) -> Vec<F> {
+    // a constant round poly has no p(1) to drop
+    if round_poly.len() < 2 {
+        round_poly.to_vec()
+    } else {
+        [&round_poly[..1], &round_poly[2..]].concat()
+    }
+}
+
+impl<F: PrimeField> SumcheckProof<F> {
+    pub fn compress(&self) -> CompressedSumcheckProof<F> {
+        CompressedSumcheckProof {
+            claimed_sum: self.claimed_sum,
+            round_polys: self
+                .round_polys
+                .iter()
+                .map(|round_poly| compress_round_poly(round_poly))
+                .collect(),
+        }
+    }
+}
+
+// what the round polynomials are computed from: fix variable 0 at a point and sum the rest.
+// the default round_polynomial/fold go through partial_evaluate, which clones every table
+// for each of the degree + 1 points. the table backed polys override them with the
+// linear time versions
+pub trait SumcheckPoly<F: Field>: Clone {
+    fn n_vars(&self) -> usize;
+    fn get_degree(&self) -> usize;
+    fn partial_evaluate(&self, value: &F) -> Self;
+    fn sum_over_hypercube(&self) -> F;
+
+    // evaluations at 0, 1, ..., degree of the sum over all but variable 0
+    fn round_polynomial(&self) -> Vec<F> {
+        get_round_partial_polynomial_proof_gkr(self)
+    }
+
+    // fixes variable 0 to the round challenge
+    fn fold(&mut self, challenge: &F) {
+        *self = self.partial_evaluate(challenge);
+    }
+}
+
+impl<F: Field> SumcheckPoly<F> for MultilinearPoly<F> {
+    fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    fn get_degree(&self) -> usize {
+        1
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        MultilinearPoly::partial_evaluate(self, 0, value)
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        self.evals.iter().sum()
+    }
+
+    fn round_polynomial(&self) -> Vec<F> {
+        round_evaluations(&[self], &[(F::one(), vec![0])], 1)
+    }
+
+    fn fold(&mut self, challenge: &F) {
+        self.fold_in_place(challenge);
+    }
+}
+
+impl<F: PrimeField> SumcheckPoly<F> for SumPoly<F> {
+    fn n_vars(&self) -> usize {
+        self.polys[0].evaluation[0].n_vars
+    }
+
+    fn get_degree(&self) -> usize {
+        SumPoly::get_degree(self)
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        SumPoly::partial_evaluate(self, value)
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        self.reduce().iter().sum()
+    }
+
+    fn round_polynomial(&self) -> Vec<F> {
+        let mut tables = vec![];
+        let mut terms = vec![];
+
+        for product_poly in self.polys.iter() {
+            let indices = (tables.len()..tables.len() + product_poly.evaluation.len()).collect();
+            tables.extend(product_poly.evaluation.iter());
+            terms.push((F::one(), indices));
+        }
+
+        round_evaluations(&tables, &terms, SumcheckPoly::get_degree(self))
+    }
+
+    fn fold(&mut self, challenge: &F) {
+        for product_poly in self.polys.iter_mut() {
+            for poly in product_poly.evaluation.iter_mut() {
+                poly.fold_in_place(challenge);
+            }
+        }
+    }
+}
+
+impl<F: Field> SumcheckPoly<F> for VirtualPoly<F> {
+    fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    fn get_degree(&self) -> usize {
+        VirtualPoly::get_degree(self)
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        VirtualPoly::partial_evaluate(self, value)
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        VirtualPoly::sum_over_hypercube(self)
+    }
+
+    fn round_polynomial(&self) -> Vec<F> {
+        let tables: Vec<_> = self.tables.iter().collect();
+
+        round_evaluations(&tables, &self.terms, VirtualPoly::get_degree(self))
+    }
+
+    fn fold(&mut self, challenge: &F) {
+        for table in self.tables.iter_mut() {
+            table.fold_in_place(challenge);
+        }
+        self.n_vars -= 1;
+    }
+}
+
+// a poly over B whose fold at a challenge from an extension E lands in E, so the sumcheck
+// can leave the base field after round 0, see SumcheckProver::prove_over_extension
+pub trait FoldInto<E: Field> {
+    type Folded: SumcheckPoly<E>;
+
+    fn fold_into(&self, challenge: &E) -> Self::Folded;
+}
+
+impl<B: PrimeField, E: Field<BasePrimeField = B>> FoldInto<E> for MultilinearPoly<B> {
+    type Folded = MultilinearPoly<E>;
+
+    fn fold_into(&self, challenge: &E) -> MultilinearPoly<E> {
+        MultilinearPoly::fold_into(self, challenge)
+    }
+}
+
+impl<B: PrimeField, E: Field<BasePrimeField = B>> FoldInto<E> for VirtualPoly<B> {
+    type Folded = VirtualPoly<E>;
+
+    fn fold_into(&self, challenge: &E) -> VirtualPoly<E> {
+        VirtualPoly {
+            tables: self
+                .tables
+                .iter()
+                .map(|table| MultilinearPoly::fold_into(table, challenge))
+                .collect(),
+            terms: self
+                .terms
+                .iter()
+                .map(|(coefficient, indices)| {
+                    (E::from_base_prime_field(*coefficient), indices.clone())
+                })
+                .collect(),
+            n_vars: self.n_vars - 1,
+        }
+    }
+}
+
+// reference prover: only the required methods, so rounds go through partial_evaluate.
+// the tests check the linear-time rounds against it and the benchmark times both
+#[derive(Clone)]
+pub struct PartialEvaluation<P>(pub P);
+
+impl<F: Field, P: SumcheckPoly<F>> SumcheckPoly<F> for PartialEvaluation<P> {
+    fn n_vars(&self) -> usize {
+        self.0.n_vars()
+    }
+
+    fn get_degree(&self) -> usize {
+        self.0.get_degree()
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        PartialEvaluation(self.0.partial_evaluate(value))
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        self.0.sum_over_hypercube()
+    }
+}
+
+// below this many pairs the rayon overhead is not worth it
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 1 << 10;
+
+// linear time round polynomial. every table is a line in variable 0 between the pair
+// (evals[i], evals[i + half]), so its value at x = 0..=degree is stepped out from the pair
+// and the terms are multiplied pointwise. O(degree * 2^n) per round, no clones
+pub(crate) fn round_evaluations<F: Field>(
+    tables: &[&MultilinearPoly<F>],
+    terms: &[(F, Vec<usize>)],
+    degree: usize,
+) -> Vec<F> {
+    let tables: Vec<&[F]> = tables.iter().map(|table| table.evals.as_slice()).collect();
+
+    table_round_evaluations(&tables, terms, degree)
 }
 
-pub struct GkrVerify<F: PrimeField> {
-    pub verified: bool,
-    pub final_claimed_sum: F,
-    pub random_challenges: Vec<F>,
+// round_evaluations on bare evals
+pub(crate) fn table_round_evaluations<F: Field>(
+    tables: &[&[F]],
+    terms: &[(F, Vec<usize>)],
+    degree: usize,
+) -> Vec<F> {
+    #[cfg(feature = "parallel")]
+    if tables[0].len() / 2 >= PARALLEL_THRESHOLD {
+        return parallel_round_evaluations(tables, terms, degree);
+    }
+
+    sequential_round_evaluations(tables, terms, degree)
+}
+
+fn sequential_round_evaluations<F: Field>(
+    tables: &[&[F]],
+    terms: &[(F, Vec<usize>)],
+    degree: usize,
+) -> Vec<F> {
+    let mut result = vec![F::zero(); degree + 1];
+    let mut table_points = vec![vec![F::zero(); degree + 1]; tables.len()];
+
+    for i in 0..tables[0].len() / 2 {
+        add_pair_evaluations(tables, terms, i, &mut table_points, &mut result);
+    }
+
+    result
+}
+
+// field addition is exact, so splitting the pairs across threads gives the same sums
+#[cfg(feature = "parallel")]
+fn parallel_round_evaluations<F: Field>(
+    tables: &[&[F]],
+    terms: &[(F, Vec<usize>)],
+    degree: usize,
+) -> Vec<F> {
+    (0..tables[0].len() / 2)
+        .into_par_iter()
+        .fold(
+            || {
+                (
+                    vec![F::zero(); degree + 1],
+                    vec![vec![F::zero(); degree + 1]; tables.len()],
+                )
+            },
+            |(mut result, mut table_points), i| {
+                add_pair_evaluations(tables, terms, i, &mut table_points, &mut result);
+                (result, table_points)
+            },
+        )
+        .map(|(result, _)| result)
+        .reduce(
+            || vec![F::zero(); degree + 1],
+            |a, b| a.iter().zip(b.iter()).map(|(a, b)| *a + *b).collect(),
+        )
+}
+
+fn add_pair_evaluations<F: Field>(
+    tables: &[&[F]],
+    terms: &[(F, Vec<usize>)],
+    i: usize,
+    table_points: &mut [Vec<F>],
+    result: &mut [F],
+) {
+    let half = tables[0].len() / 2;
+
+    for (table, points) in tables.iter().zip(table_points.iter_mut()) {
+        let low = table[i];
+        let step = table[i + half] - low;
+
+        let mut value = low;
+        for point in points.iter_mut() {
+            *point = value;
+            value += step;
+        }
+    }
+
+    for (coefficient, indices) in terms.iter() {
+        for (x, eval) in result.iter_mut().enumerate() {
+            *eval += *coefficient
+                * indices
+                    .iter()
+                    .map(|index| table_points[*index][x])
+                    .product::<F>();
+        }
+    }
 }
 
 fn fq_vec_to_bytes<F: PrimeField>(values: &[F]) -> Vec<u8> {
@@ -31,201 +339,572 @@ fn fq_vec_to_bytes<F: PrimeField>(values: &[F]) -> Vec<u8> {
         .collect()
 }
 
-fn prove<F: PrimeField>(poly: &MultilinearPoly<F>, claimed_sum: F) -> Proof<F> {
-    let mut round_polys = vec![];
+// the sumcheck rounds only reduce the claimed sum to a claim that the polynomial
+// evaluates to `value` at `point`. who checks it (the full poly, an oracle, a
+// commitment opening) is up to the caller
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvaluationClaim<F: Field> {
+    pub point: Vec<F>,
+    pub value: F,
+}
+
+impl<F: Field> EvaluationClaim<F> {
+    pub fn check<O: FnOnce(&[F]) -> F>(&self, oracle: O) -> bool {
+        oracle(&self.point) == self.value
+    }
+}
 
-    let mut transcript = Transcript::new();
-    // &[u8]
-    // [&[u8], &[u8], ...]
-    // [[1, 2], [3, 4]]
-    // [1, 2, 3, 4]
-    transcript.append(
-        poly.evals
-            .iter()
-            .flat_map(|f| f.into_bigint().to_bytes_be())
-            .collect::<Vec<_>>()
-            .as_slice(),
-    );
-    transcript.append(claimed_sum.into_bigint().to_bytes_be().as_slice());
-
-    let mut poly = poly.clone();
-
-    for _ in 0..poly.n_vars {
-        let round_poly: [F; 2] = [
-            poly.partial_evaluate(0, &F::zero()).evals.iter().sum(),
-            poly.partial_evaluate(0, &F::one()).evals.iter().sum(),
-        ];
-
-        transcript.append(
-            round_poly
-                .iter()
-                .flat_map(|f| f.into_bigint().to_bytes_be())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        );
+// the prover side of one sumcheck as a state machine: send round_poly, receive the
+// challenge, repeat until done. the challenges can come from a transcript, as prove_rounds
+// does, or from a verifier on the other end of a channel
+pub struct SumcheckProverState<F: Field, P: SumcheckPoly<F>> {
+    poly: P,
+    challenges: Vec<F>,
+}
 
-        round_polys.push(round_poly);
+impl<F: Field, P: SumcheckPoly<F>> SumcheckProverState<F, P> {
+    pub fn new(poly: &P) -> Self {
+        Self::resume(poly.clone(), vec![])
+    }
 
-        let challenge = transcript.get_random_challenge();
+    // poly already folded at challenges, e.g. a base field poly folded into an extension
+    pub(crate) fn resume(poly: P, mut challenges: Vec<F>) -> Self {
+        challenges.reserve(poly.n_vars());
+
+        Self { poly, challenges }
+    }
 
-        poly = poly.partial_evaluate(0, &challenge);
+    pub fn is_done(&self) -> bool {
+        self.poly.n_vars() == 0
     }
 
-    Proof {
-        claimed_sum,
-        round_polys,
+    // the message for the current round, evaluations at 0, 1, ..., degree
+    pub fn round_poly(&self) -> Vec<F> {
+        if self.is_done() {
+            panic!("all rounds have been sent");
+        }
+
+        self.poly.round_polynomial()
+    }
+
+    pub fn receive_challenge(&mut self, challenge: F) {
+        if self.is_done() {
+            panic!("all rounds have been sent");
+        }
+
+        self.poly.fold(&challenge);
+        self.challenges.push(challenge);
+    }
+
+    // the challenges and the fully folded poly
+    pub fn finish(self) -> (Vec<F>, P) {
+        if !self.is_done() {
+            panic!("the sumcheck still has rounds left");
+        }
+
+        (self.challenges, self.poly)
     }
 }
 
-fn verify<F: PrimeField>(poly: &MultilinearPoly<F>, proof: &Proof<F>) -> bool {
-    if proof.round_polys.len() != poly.n_vars {
-        return false;
+// the verifier side: every received round is checked against the running claim, and the
+// next claim is the round poly at the challenge. the challenge is injected, so the same
+// machine runs with transcript, rng or hand picked randomness
+#[derive(Clone, Debug)]
+pub struct SumcheckVerifierState<F: Field> {
+    n_vars: usize,
+    // round i sums over {0, ..., arities[i] - 1} and has degrees[i] + 1 values
+    arities: Vec<usize>,
+    degrees: Vec<usize>,
+    claim: F,
+    round_poly: Option<Vec<F>>,
+    challenges: Vec<F>,
+}
+
+impl<F: Field> SumcheckVerifierState<F> {
+    // n_vars and degree come from the verifier's own view of the polynomial
+    pub fn new(claimed_sum: F, n_vars: usize, degree: usize) -> Self {
+        Self::over_domain(claimed_sum, &vec![2; n_vars], degree)
     }
 
-    let mut challenges = vec![];
+    // a sumcheck over {0, ..., k_0 - 1} x {0, ..., k_1 - 1} x ..., see domain_sum_check.
+    // term_degree bounds the tables per term, so round i has degree
+    // term_degree * (arities[i] - 1)
+    pub fn over_domain(claimed_sum: F, arities: &[usize], term_degree: usize) -> Self {
+        Self {
+            n_vars: arities.len(),
+            arities: arities.to_vec(),
+            degrees: arities
+                .iter()
+                .map(|arity| term_degree * (arity - 1))
+                .collect(),
+            claim: claimed_sum,
+            round_poly: None,
+            challenges: Vec::with_capacity(arities.len()),
+        }
+    }
 
-    let mut transcript = Transcript::new();
-    transcript.append(
-        poly.evals
-            .iter()
-            .flat_map(|f| f.into_bigint().to_bytes_be())
-            .collect::<Vec<_>>()
-            .as_slice(),
-    );
-    transcript.append(proof.claimed_sum.into_bigint().to_bytes_be().as_slice());
+    pub fn claim(&self) -> F {
+        self.claim
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.challenges.len() == self.n_vars
+    }
+
+    // checks the round and returns it as accepted, ready to be absorbed
+    pub fn receive_round_poly(&mut self, round_poly: Vec<F>) -> Result<&[F], SumcheckError> {
+        if self.round_poly.is_some() {
+            panic!("the last round has not been challenged yet");
+        }
+
+        let round = self.challenges.len();
 
-    let mut claimed_sum = proof.claimed_sum;
+        if self.is_done() {
+            return Err(SumcheckError::WrongNumberOfRounds {
+                expected: self.n_vars,
+                got: round + 1,
+            });
+        }
+
+        let degree = self.degrees[round];
+        if round_poly.len() != degree + 1 {
+            return Err(SumcheckError::WrongRoundPolyLength {
+                round,
+                expected: degree + 1,
+                got: round_poly.len(),
+            });
+        }
 
-    for round_poly in &proof.round_polys {
-        if claimed_sum != round_poly.iter().sum() {
-            return false;
+        // a degree 0 round poly is constant, so it takes p(0) all over the domain
+        let sum: F = (0..self.arities[round])
+            .map(|x| *round_poly.get(x).unwrap_or(&round_poly[0]))
+            .sum();
+        if sum != self.claim {
+            return Err(SumcheckError::RoundSumMismatch { round });
         }
 
-        transcript.append(
-            round_poly
+        Ok(self.round_poly.insert(round_poly))
+    }
+
+    // rebuilds p(1) = claim - p(0) - p(2) - ... - p(arity - 1) first
+    pub fn receive_compressed_round_poly(
+        &mut self,
+        round_poly: Vec<F>,
+    ) -> Result<&[F], SumcheckError> {
+        let round = self.challenges.len();
+        if self.is_done() || self.degrees[round] == 0 {
+            return self.receive_round_poly(round_poly);
+        }
+
+        if round_poly.len() != self.degrees[round] {
+            return Err(SumcheckError::WrongRoundPolyLength {
+                round,
+                expected: self.degrees[round],
+                got: round_poly.len(),
+            });
+        }
+
+        // the compressed poly is p(0), p(2), p(3), ...
+        let p_1 = self.claim
+            - round_poly[0]
+            - round_poly[1..self.arities[round] - 1]
                 .iter()
-                .flat_map(|f| f.into_bigint().to_bytes_be())
-                .collect::<Vec<_>>()
-                .as_slice(),
-        );
+                .copied()
+                .sum::<F>();
+        self.receive_round_poly([&round_poly[..1], &[p_1], &round_poly[1..]].concat())
+    }
 
-        let challenge = transcript.get_random_challenge();
-        claimed_sum = round_poly[0] + challenge * (round_poly[1] - round_poly[0]);
-        challenges.push(challenge);
+    pub fn challenge_with(&mut self, challenge: F) {
+        let round_poly = self.round_poly.take().expect("no round poly to challenge");
+
+        self.claim = barycentric_evaluate(&round_poly, challenge);
+        self.challenges.push(challenge);
     }
 
-    if claimed_sum != poly.evaluate(challenges) {
-        return false;
+    pub fn sample_challenge<R: Rng>(&mut self, rng: &mut R) -> F {
+        let challenge = F::rand(rng);
+        self.challenge_with(challenge);
+
+        challenge
     }
 
-    true
+    // the evaluation claim that is still pending
+    pub fn finish(self) -> Result<EvaluationClaim<F>, SumcheckError> {
+        if !self.is_done() {
+            return Err(SumcheckError::WrongNumberOfRounds {
+                expected: self.n_vars,
+                got: self.challenges.len(),
+            });
+        }
+
+        Ok(EvaluationClaim {
+            point: self.challenges,
+            value: self.claim,
+        })
+    }
 }
 
-pub fn gkr_prove<F: PrimeField>(
-    claimed_sum: F,
-    composed_polynomial: &SumPoly<F>,
-    transcript: &mut Transcript<F>,
-) -> GkrProof<F> {
-    let num_rounds = composed_polynomial.polys[0].evaluation[0].n_vars;
-    let mut proof_polynomials = Vec::with_capacity(num_rounds);
-    let mut random_challenges = Vec::with_capacity(num_rounds);
-    let mut current_poly = composed_polynomial.clone();
+pub struct SumcheckProver;
+
+impl SumcheckProver {
+    // the transcript should already hold whatever binds the prover to poly. the returned
+    // claim is the poly evaluated at the challenges, which the caller still has to prove
+    pub fn prove<F: PrimeField, P: SumcheckPoly<F>>(
+        poly: &P,
+        claimed_sum: F,
+        transcript: &mut Transcript<F>,
+    ) -> (SumcheckProof<F>, EvaluationClaim<F>) {
+        let (proof, challenges, current_poly) = Self::prove_rounds(poly, claimed_sum, transcript);
+
+        let claim = EvaluationClaim {
+            point: challenges,
+            value: current_poly.sum_over_hypercube(),
+        };
+
+        (proof, claim)
+    }
 
-    for _ in 0..num_rounds {
-        let proof_poly = get_round_partial_polynomial_proof_gkr(&current_poly); //this is f(b) then f(c)
+    // also hands back the fully folded poly, for callers that need more than its sum
+    pub(crate) fn prove_rounds<F: PrimeField, P: SumcheckPoly<F>>(
+        poly: &P,
+        claimed_sum: F,
+        transcript: &mut Transcript<F>,
+    ) -> (SumcheckProof<F>, Vec<F>, P) {
+        let mut state = SumcheckProverState::new(poly);
+        let mut round_polys = Vec::with_capacity(poly.n_vars());
 
-        transcript.append(&fq_vec_to_bytes(&proof_poly));
+        transcript.append(&fq_vec_to_bytes(&[claimed_sum]));
 
-        proof_polynomials.push(proof_poly);
+        Self::run_rounds(&mut state, &mut round_polys, transcript);
 
-        let random_challenge = transcript.get_random_challenge(); //this is b and c aka r1 r2
+        let (challenges, current_poly) = state.finish();
 
-        random_challenges.push(random_challenge);
+        let proof = SumcheckProof {
+            claimed_sum,
+            round_polys,
+        };
 
-        current_poly = current_poly.partial_evaluate(&random_challenge);
+        (proof, challenges, current_poly)
     }
 
-    GkrProof {
-        proof_polynomials,
-        claimed_sum,
-        random_challenges,
+    // tables over a small prime field B with challenges from an extension E. round 0 is
+    // computed entirely in B, the first challenge folds the poly into E and only the later,
+    // half sized rounds pay for extension arithmetic. the transcript stays over B and draws
+    // one B challenge per coordinate of E. SumcheckVerifier::verify checks the proof, with
+    // E = B it is the proof prove gives
+    pub fn prove_over_extension<B, E, P>(
+        poly: &P,
+        claimed_sum: B,
+        transcript: &mut Transcript<B>,
+    ) -> (SumcheckProof<E>, EvaluationClaim<E>)
+    where
+        B: PrimeField,
+        E: Field<BasePrimeField = B>,
+        P: SumcheckPoly<B> + FoldInto<E>,
+    {
+        let claimed_sum = E::from_base_prime_field(claimed_sum);
+        let mut round_polys = Vec::with_capacity(poly.n_vars());
+
+        transcript.append(&field_vec_to_bytes(&[claimed_sum]));
+
+        if poly.n_vars() == 0 {
+            let claim = EvaluationClaim {
+                point: vec![],
+                value: E::from_base_prime_field(poly.sum_over_hypercube()),
+            };
+
+            return (
+                SumcheckProof {
+                    claimed_sum,
+                    round_polys,
+                },
+                claim,
+            );
+        }
+
+        let round_poly: Vec<E> = poly
+            .round_polynomial()
+            .into_iter()
+            .map(E::from_base_prime_field)
+            .collect();
+
+        transcript.append(&field_vec_to_bytes(&round_poly));
+        round_polys.push(round_poly);
+
+        let challenge = transcript.get_extension_challenge();
+        let mut state = SumcheckProverState::resume(poly.fold_into(&challenge), vec![challenge]);
+
+        Self::run_rounds(&mut state, &mut round_polys, transcript);
+
+        let (point, current_poly) = state.finish();
+
+        let proof = SumcheckProof {
+            claimed_sum,
+            round_polys,
+        };
+
+        let claim = EvaluationClaim {
+            point,
+            value: current_poly.sum_over_hypercube(),
+        };
+
+        (proof, claim)
+    }
+
+    // the rounds left in state, with every round absorbed and the challenge drawn from the
+    // transcript. the rounds may live in an extension E of the transcript field
+    fn run_rounds<B: PrimeField, E: Field<BasePrimeField = B>, P: SumcheckPoly<E>>(
+        state: &mut SumcheckProverState<E, P>,
+        round_polys: &mut Vec<Vec<E>>,
+        transcript: &mut Transcript<B>,
+    ) {
+        while !state.is_done() {
+            let round_poly = state.round_poly();
+
+            transcript.append(&field_vec_to_bytes(&round_poly));
+
+            round_polys.push(round_poly);
+
+            state.receive_challenge(transcript.get_extension_challenge());
+        }
     }
 }
 
-pub fn gkr_verify<F: PrimeField>(
-    round_polys: Vec<Vec<F>>,
-    mut claimed_sum: F,
-    transcript: &mut Transcript<F>,
-) -> GkrVerify<F> {
-    let mut random_challenges = Vec::new();
-
-    for round_poly in round_polys {
-        let f_b_0 = round_poly[0];
-        let f_b_1 = round_poly[1];
-
-        if f_b_0 + f_b_1 != claimed_sum {
-            return GkrVerify {
-                verified: false,
-                final_claimed_sum: F::zero(),
-                random_challenges: vec![F::zero()],
-            };
+#[derive(Clone, Debug, PartialEq)]
+pub enum SumcheckError {
+    WrongNumberOfRounds {
+        expected: usize,
+        got: usize,
+    },
+    WrongRoundPolyLength {
+        round: usize,
+        expected: usize,
+        got: usize,
+    },
+    RoundSumMismatch {
+        round: usize,
+    },
+    WrongNumberOfInstances {
+        expected: usize,
+        got: usize,
+    },
+    FinalEvaluationMismatch,
+    InvalidMaskOpening,
+}
+
+impl fmt::Display for SumcheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SumcheckError::WrongNumberOfRounds { expected, got } => {
+                write!(f, "expected {} rounds, got {}", expected, got)
+            }
+            SumcheckError::WrongRoundPolyLength {
+                round,
+                expected,
+                got,
+            } => write!(
+                f,
+                "round {} polynomial should have {} evaluations, got {}",
+                round, expected, got
+            ),
+            SumcheckError::RoundSumMismatch { round } => {
+                write!(f, "round {} polynomial does not sum to the claim", round)
+            }
+            SumcheckError::WrongNumberOfInstances { expected, got } => {
+                write!(f, "expected {} instances, got {}", expected, got)
+            }
+            SumcheckError::FinalEvaluationMismatch => {
+                write!(f, "final evaluations do not match the last round")
+            }
+            SumcheckError::InvalidMaskOpening => {
+                write!(f, "masking polynomial opening does not verify")
+            }
         }
+    }
+}
 
-        transcript.append(&fq_vec_to_bytes(&round_poly));
+impl std::error::Error for SumcheckError {}
+
+pub struct SumcheckVerifier;
+
+impl SumcheckVerifier {
+    // checks the rounds without touching the polynomial, so the verifier work does not depend
+    // on its size. n_vars and degree come from the verifier's own view of the polynomial,
+    // never from the proof. returns the evaluation claim that is still pending
+    // the proof may be over an extension E of the transcript field, as extension_sum_check
+    // produces. for a prime field E = B
+    pub fn verify<B: PrimeField, E: Field<BasePrimeField = B>>(
+        proof: &SumcheckProof<E>,
+        n_vars: usize,
+        degree: usize,
+        transcript: &mut Transcript<B>,
+    ) -> Result<EvaluationClaim<E>, SumcheckError> {
+        Self::verify_rounds(
+            proof.claimed_sum,
+            &proof.round_polys,
+            n_vars,
+            degree,
+            false,
+            transcript,
+        )
+    }
 
-        let r_c = transcript.get_random_challenge();
+    // rebuilds p(1) = claim - p(0) before each round is checked, so the transcript and the
+    // returned claim match the uncompressed proof
+    pub fn verify_compressed<F: PrimeField>(
+        proof: &CompressedSumcheckProof<F>,
+        n_vars: usize,
+        degree: usize,
+        transcript: &mut Transcript<F>,
+    ) -> Result<EvaluationClaim<F>, SumcheckError> {
+        Self::verify_rounds(
+            proof.claimed_sum,
+            &proof.round_polys,
+            n_vars,
+            degree,
+            true,
+            transcript,
+        )
+    }
 
-        random_challenges.push(r_c);
+    // drives SumcheckVerifierState with transcript challenges. the rounds and challenges
+    // may live in an extension E of the transcript field, see extension_sum_check
+    pub(crate) fn verify_rounds<B: PrimeField, E: Field<BasePrimeField = B>>(
+        claimed_sum: E,
+        round_polys: &[Vec<E>],
+        n_vars: usize,
+        degree: usize,
+        compressed: bool,
+        transcript: &mut Transcript<B>,
+    ) -> Result<EvaluationClaim<E>, SumcheckError> {
+        Self::run_rounds(
+            SumcheckVerifierState::new(claimed_sum, n_vars, degree),
+            round_polys,
+            compressed,
+            transcript,
+        )
+    }
 
-        let round_uni_points = round_poly
-            .iter()
-            .enumerate()
-            .map(|(i, y)| {
-                let x = F::from(i as u64);
+    // verify_rounds for any state, e.g. one over a domain
+    pub(crate) fn run_rounds<B: PrimeField, E: Field<BasePrimeField = B>>(
+        mut state: SumcheckVerifierState<E>,
+        round_polys: &[Vec<E>],
+        compressed: bool,
+        transcript: &mut Transcript<B>,
+    ) -> Result<EvaluationClaim<E>, SumcheckError> {
+        if round_polys.len() != state.n_vars {
+            return Err(SumcheckError::WrongNumberOfRounds {
+                expected: state.n_vars,
+                got: round_polys.len(),
+            });
+        }
 
-                (x, *y)
-            })
-            .collect();
+        let claimed_sum = state.claim;
+        transcript.append(&field_vec_to_bytes(&[claimed_sum]));
 
-        let round_uni_poly = UnivariatePolynomial::interpolate(round_uni_points);
+        for round_poly in round_polys.iter() {
+            let round_poly = if compressed {
+                state.receive_compressed_round_poly(round_poly.clone())?
+            } else {
+                state.receive_round_poly(round_poly.clone())?
+            };
+
+            transcript.append(&field_vec_to_bytes(round_poly));
 
-        claimed_sum = round_uni_poly.evaluate(r_c); //next expected sum
+            state.challenge_with(transcript.get_extension_challenge());
+        }
+
+        state.finish()
     }
+}
 
-    GkrVerify {
-        verified: true,
-        final_claimed_sum: claimed_sum,
-        random_challenges,
+fn poly_to_bytes<F: PrimeField>(poly: &MultilinearPoly<F>) -> Vec<u8> {
+    poly.evals
+        .iter()
+        .flat_map(|f| f.into_bigint().to_bytes_be())
+        .collect()
+}
+
+pub fn prove<F: PrimeField>(poly: &MultilinearPoly<F>, claimed_sum: F) -> SumcheckProof<F> {
+    let mut transcript = Transcript::new();
+    // &[u8]
+    // [&[u8], &[u8], ...]
+    // [[1, 2], [3, 4]]
+    // [1, 2, 3, 4]
+    transcript.append(&poly_to_bytes(poly));
+
+    prove_with_transcript(poly, claimed_sum, &mut transcript)
+}
+
+// the transcript should already hold whatever binds the prover to poly, e.g. a commitment
+pub fn prove_with_transcript<F: PrimeField>(
+    poly: &MultilinearPoly<F>,
+    claimed_sum: F,
+    transcript: &mut Transcript<F>,
+) -> SumcheckProof<F> {
+    SumcheckProver::prove(poly, claimed_sum, transcript).0
+}
+
+pub fn verify<F: PrimeField>(poly: &MultilinearPoly<F>, proof: &SumcheckProof<F>) -> bool {
+    let mut transcript = Transcript::new();
+    transcript.append(&poly_to_bytes(poly));
+
+    verify_with_oracle(poly.n_vars, proof, &mut transcript, |point| {
+        poly.evaluate(point.to_vec())
+    })
+}
+
+pub fn verify_with_oracle<F: PrimeField, O: FnOnce(&[F]) -> F>(
+    n_vars: usize,
+    proof: &SumcheckProof<F>,
+    transcript: &mut Transcript<F>,
+    oracle: O,
+) -> bool {
+    match verify_claim(n_vars, proof, transcript) {
+        Ok(claim) => claim.check(oracle),
+        Err(_) => false,
     }
 }
 
-fn get_round_partial_polynomial_proof_gkr<F: PrimeField>(composed_poly: &SumPoly<F>) -> Vec<F> {
+// a multilinear poly, so every round polynomial is a line
+pub fn verify_claim<F: PrimeField>(
+    n_vars: usize,
+    proof: &SumcheckProof<F>,
+    transcript: &mut Transcript<F>,
+) -> Result<EvaluationClaim<F>, SumcheckError> {
+    SumcheckVerifier::verify(proof, n_vars, 1, transcript)
+}
+
+fn get_round_partial_polynomial_proof_gkr<F: Field, P: SumcheckPoly<F>>(
+    composed_poly: &P,
+) -> Vec<F> {
     let degree = composed_poly.get_degree();
 
     (0..=degree)
         .map(|i| {
             let partial_poly = composed_poly.partial_evaluate(&F::from(i as u64));
 
-            partial_poly.reduce().iter().sum()
+            partial_poly.sum_over_hypercube()
         })
         .collect()
 }
 
-fn get_round_partial_polynomial_proof<F: PrimeField>(polynomial: &[F]) -> Vec<F> {
-    let mid_point = polynomial.len() / 2;
-    let (zeros, ones) = polynomial.split_at(mid_point);
-
-    let poly_proof = vec![zeros.iter().sum(), ones.iter().sum()];
-
-    poly_proof
-}
-
 #[cfg(test)]
 mod test {
-    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::tests::{committed_transcript, to_field};
     use crate::libs::multilinear_poly::MultilinearPoly;
-    use crate::libs::sum_check_protocol::{prove, verify};
+    use crate::libs::multilinear_poly::{ProductPoly, SumPoly};
+    use crate::libs::sum_check_protocol::{
+        prove, prove_with_transcript, verify, verify_claim, verify_with_oracle,
+        CompressedSumcheckProof, PartialEvaluation, SumcheckError, SumcheckPoly, SumcheckProof,
+        SumcheckProver, SumcheckProverState, SumcheckVerifier, SumcheckVerifierState,
+    };
+    use crate::libs::virtual_poly::VirtualPoly;
     use ark_bn254::Fr;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
     #[test]
     fn test_sumcheck_protocol() {
@@ -234,6 +913,325 @@ mod test {
 
         dbg!(verify(&poly, &proof));
     }
+
+    const COMMITMENT: &[u8] = b"commitment to 2ab + 3bc";
+
+    #[test]
+    fn it_verifies_without_the_polynomial() {
+        let poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let proof =
+            prove_with_transcript(&poly, Fr::from(10), &mut committed_transcript(COMMITMENT));
+
+        let claim = verify_claim(3, &proof, &mut committed_transcript(COMMITMENT)).unwrap();
+
+        assert_eq!(claim.point.len(), 3);
+        assert_eq!(claim.value, poly.evaluate(claim.point.clone()));
+        assert!(claim.check(|point| poly.evaluate(point.to_vec())));
+        assert!(verify_with_oracle(
+            3,
+            &proof,
+            &mut committed_transcript(COMMITMENT),
+            |point| poly.evaluate(point.to_vec())
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_sum_or_wrong_oracle() {
+        let poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let other = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 6]));
+
+        let wrong_sum =
+            prove_with_transcript(&poly, Fr::from(11), &mut committed_transcript(COMMITMENT));
+        assert_eq!(
+            verify_claim(3, &wrong_sum, &mut committed_transcript(COMMITMENT)),
+            Err(SumcheckError::RoundSumMismatch { round: 0 })
+        );
+
+        let proof =
+            prove_with_transcript(&poly, Fr::from(10), &mut committed_transcript(COMMITMENT));
+        assert_eq!(
+            verify_claim(2, &proof, &mut committed_transcript(COMMITMENT)),
+            Err(SumcheckError::WrongNumberOfRounds {
+                expected: 2,
+                got: 3
+            })
+        );
+        assert!(!verify_with_oracle(
+            3,
+            &proof,
+            &mut committed_transcript(COMMITMENT),
+            |point| other.evaluate(point.to_vec())
+        ));
+    }
+
+    #[test]
+    fn proof_round_trips_through_bytes() {
+        let poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let (proof, claim) = SumcheckProver::prove(&poly, Fr::from(10), &mut Transcript::new());
+
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = SumcheckProof::<Fr>::deserialize_compressed(bytes.as_slice()).unwrap();
+
+        assert_eq!(decoded, proof);
+        assert_eq!(
+            SumcheckVerifier::verify(&decoded, 3, 1, &mut Transcript::new()),
+            Ok(claim)
+        );
+    }
+
+    #[test]
+    fn compressed_proof_drops_one_element_per_round() {
+        let mut poly = VirtualPoly::new(3);
+        let a = poly.add_table(MultilinearPoly::new(to_field(vec![1, 2, 3, 4, 5, 6, 7, 8])));
+        let b = poly.add_table(MultilinearPoly::new(to_field(vec![0, 5, 1, 2, 9, 9, 4, 1])));
+        poly.add_term(Fr::from(3), vec![a, b, b]);
+        poly.add_term(-Fr::from(7), vec![a]);
+
+        let claimed_sum = poly.sum_over_hypercube();
+        let (proof, claim) = SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+        let compressed = proof.compress();
+
+        assert!(compressed
+            .round_polys
+            .iter()
+            .all(|round_poly| round_poly.len() == 3));
+
+        let mut full_bytes = vec![];
+        proof.serialize_compressed(&mut full_bytes).unwrap();
+        let mut compressed_bytes = vec![];
+        compressed
+            .serialize_compressed(&mut compressed_bytes)
+            .unwrap();
+        assert_eq!(
+            full_bytes.len() - compressed_bytes.len(),
+            3 * Fr::from(0).compressed_size()
+        );
+
+        assert_eq!(
+            SumcheckVerifier::verify_compressed(&compressed, 3, 3, &mut Transcript::new()),
+            Ok(claim)
+        );
+
+        // a wrong p(0) moves the rebuilt p(1) with it, so it is caught by the final evaluation
+        let mut tampered = compressed.clone();
+        tampered.round_polys[0][0] += Fr::from(1);
+        assert!(
+            SumcheckVerifier::verify_compressed(&tampered, 3, 3, &mut Transcript::new())
+                .map_or(true, |tampered_claim| !tampered_claim
+                    .check(|point| poly.evaluate(point)))
+        );
+
+        let wrong_sum = CompressedSumcheckProof {
+            claimed_sum: claimed_sum + Fr::from(1),
+            round_polys: compressed.round_polys.clone(),
+        };
+        assert!(
+            SumcheckVerifier::verify_compressed(&wrong_sum, 3, 3, &mut Transcript::new())
+                .map_or(true, |wrong_claim| !wrong_claim
+                    .check(|point| poly.evaluate(point)))
+        );
+
+        assert_eq!(
+            SumcheckVerifier::verify_compressed(&compressed, 3, 2, &mut Transcript::new()),
+            Err(SumcheckError::WrongRoundPolyLength {
+                round: 0,
+                expected: 2,
+                got: 3
+            })
+        );
+    }
+
+    fn assert_same_as_naive<P: SumcheckPoly<Fr>>(poly: P) {
+        let claimed_sum = poly.sum_over_hypercube();
+
+        let fast = SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+        let naive = SumcheckProver::prove(
+            &PartialEvaluation(poly),
+            claimed_sum,
+            &mut Transcript::new(),
+        );
+
+        assert_eq!(fast, naive);
+    }
+
+    #[test]
+    fn linear_time_prover_matches_partial_evaluation() {
+        assert_same_as_naive(MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5])));
+
+        assert_same_as_naive(SumPoly::new(vec![
+            ProductPoly::new(vec![to_field(vec![1, 2, 3, 4]), to_field(vec![5, 6, 7, 8])]),
+            ProductPoly::new(vec![to_field(vec![9, 1, 2, 3])]),
+        ]));
+
+        let mut virtual_poly = VirtualPoly::new(3);
+        let a =
+            virtual_poly.add_table(MultilinearPoly::new(to_field(vec![1, 2, 3, 4, 5, 6, 7, 8])));
+        let b =
+            virtual_poly.add_table(MultilinearPoly::new(to_field(vec![0, 5, 1, 2, 9, 9, 4, 1])));
+        virtual_poly.add_term(Fr::from(3), vec![a, b, b]);
+        virtual_poly.add_term(-Fr::from(7), vec![a]);
+
+        assert_same_as_naive(virtual_poly);
+    }
+
+    // the same rounds with the parallel feature switched off: sequential round evaluations
+    // and a plain folding loop
+    #[cfg(feature = "parallel")]
+    #[derive(Clone)]
+    struct Sequential(VirtualPoly<Fr>);
+
+    #[cfg(feature = "parallel")]
+    impl SumcheckPoly<Fr> for Sequential {
+        fn n_vars(&self) -> usize {
+            self.0.n_vars
+        }
+
+        fn get_degree(&self) -> usize {
+            self.0.get_degree()
+        }
+
+        fn partial_evaluate(&self, value: &Fr) -> Self {
+            Sequential(self.0.partial_evaluate(value))
+        }
+
+        fn sum_over_hypercube(&self) -> Fr {
+            self.0.sum_over_hypercube()
+        }
+
+        fn round_polynomial(&self) -> Vec<Fr> {
+            use crate::libs::sum_check_protocol::sequential_round_evaluations;
+
+            let tables: Vec<&[Fr]> = self
+                .0
+                .tables
+                .iter()
+                .map(|table| table.evals.as_slice())
+                .collect();
+            sequential_round_evaluations(&tables, &self.0.terms, self.0.get_degree())
+        }
+
+        fn fold(&mut self, challenge: &Fr) {
+            for table in self.0.tables.iter_mut() {
+                let half = table.evals.len() / 2;
+                for i in 0..half {
+                    let (low, high) = (table.evals[i], table.evals[i + half]);
+                    table.evals[i] = low + *challenge * (high - low);
+                }
+                table.evals.truncate(half);
+                table.n_vars -= 1;
+            }
+            self.0.n_vars -= 1;
+        }
+    }
+
+    // 12 variables, so the first rounds have at least PARALLEL_THRESHOLD pairs
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_prover_matches_sequential() {
+        use ark_std::{test_rng, UniformRand};
+
+        let mut rng = test_rng();
+        let mut poly = VirtualPoly::new(12);
+        for _ in 0..3 {
+            poly.add_table(MultilinearPoly::new(
+                (0..1 << 12).map(|_| Fr::rand(&mut rng)).collect(),
+            ));
+        }
+        poly.add_term(Fr::from(2), vec![0, 1, 2]);
+        poly.add_term(Fr::from(5), vec![1]);
+        let claimed_sum = poly.sum_over_hypercube();
+
+        let parallel = SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+        let sequential =
+            SumcheckProver::prove(&Sequential(poly), claimed_sum, &mut Transcript::new());
+
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn fold_matches_partial_evaluate() {
+        let mut poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let expected = poly.partial_evaluate(0, &Fr::from(9));
+
+        poly.fold_in_place(&Fr::from(9));
+
+        assert_eq!(poly, expected);
+    }
+
+    #[test]
+    fn interactive_rounds_with_verifier_randomness() {
+        let poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let mut prover = SumcheckProverState::new(&poly);
+        let mut verifier = SumcheckVerifierState::new(Fr::from(10), 3, 1);
+
+        while !prover.is_done() {
+            verifier.receive_round_poly(prover.round_poly()).unwrap();
+            prover.receive_challenge(verifier.sample_challenge(&mut rng));
+        }
+
+        let (challenges, folded) = prover.finish();
+        let claim = verifier.finish().unwrap();
+
+        assert_eq!(claim.point, challenges);
+        assert_eq!(claim.value, folded.evals[0]);
+        assert!(claim.check(|point| poly.evaluate(point.to_vec())));
+    }
+
+    #[test]
+    fn injected_challenges_replay_the_transcript() {
+        let poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let (proof, claim) = SumcheckProver::prove(&poly, Fr::from(10), &mut Transcript::new());
+
+        let mut verifier = SumcheckVerifierState::new(Fr::from(10), 3, 1);
+
+        for (round_poly, challenge) in proof.round_polys.iter().zip(claim.point.iter()) {
+            verifier.receive_round_poly(round_poly.clone()).unwrap();
+            verifier.challenge_with(*challenge);
+        }
+
+        assert_eq!(verifier.finish(), Ok(claim));
+    }
+
+    #[test]
+    fn interactive_verifier_catches_a_lying_prover() {
+        let poly = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // claims 11, and shifts p(0) so the first round still adds up
+        let mut prover = SumcheckProverState::new(&poly);
+        let mut verifier = SumcheckVerifierState::new(Fr::from(11), 3, 1);
+
+        let mut round_poly = prover.round_poly();
+        round_poly[0] += Fr::from(1);
+        verifier.receive_round_poly(round_poly).unwrap();
+        prover.receive_challenge(verifier.sample_challenge(&mut rng));
+
+        // from here on it answers honestly, which no longer matches the claim
+        assert_eq!(
+            verifier.receive_round_poly(prover.round_poly()),
+            Err(SumcheckError::RoundSumMismatch { round: 1 })
+        );
+
+        let mut short = SumcheckVerifierState::new(Fr::from(10), 3, 1);
+        assert_eq!(
+            short.receive_compressed_round_poly(vec![]),
+            Err(SumcheckError::WrongRoundPolyLength {
+                round: 0,
+                expected: 1,
+                got: 0
+            })
+        );
+        assert_eq!(
+            short.finish(),
+            Err(SumcheckError::WrongNumberOfRounds {
+                expected: 3,
+                got: 0
+            })
+        );
+    }
 }
 
 // use super::fiat_shamir::Transcript;
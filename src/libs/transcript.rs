@@ -6,6 +6,12 @@ pub struct Transcript {
     hasher: Keccak256,
 }
 
+impl Default for Transcript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Transcript {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,210 @@
+use super::multilinear_poly::{MultilinearPoly, SumPoly};
+use ark_ff::{Field, PrimeField};
+
+// sum of coefficient * product of tables, where every term picks its factors by index
+// into one shared list of tables. terms can have different degrees
+#[derive(Clone, Debug, PartialEq)]
+pub struct VirtualPoly<F: Field> {
+    pub(crate) tables: Vec<MultilinearPoly<F>>,
+    pub(crate) terms: Vec<(F, Vec<usize>)>,
+    pub(crate) n_vars: usize,
+}
+
+impl<F: Field> VirtualPoly<F> {
+    pub fn new(n_vars: usize) -> Self {
+        Self {
+            tables: vec![],
+            terms: vec![],
+            n_vars,
+        }
+    }
+
+    // returns the index terms use to refer to the table
+    pub fn add_table(&mut self, table: MultilinearPoly<F>) -> usize {
+        if table.n_vars != self.n_vars {
+            panic!("all tables must have the same number of variables");
+        }
+
+        self.tables.push(table);
+
+        self.tables.len() - 1
+    }
+
+    pub fn add_term(&mut self, coefficient: F, table_indices: Vec<usize>) {
+        if table_indices
+            .iter()
+            .any(|index| *index >= self.tables.len())
+        {
+            panic!("term refers to a table that was never added");
+        }
+
+        self.terms.push((coefficient, table_indices));
+    }
+
+    pub fn evaluate(&self, values: &[F]) -> F {
+        let table_evals: Vec<F> = self
+            .tables
+            .iter()
+            .map(|table| table.evaluate(values.to_vec()))
+            .collect();
+
+        self.terms
+            .iter()
+            .map(|(coefficient, indices)| {
+                *coefficient
+                    * indices
+                        .iter()
+                        .map(|index| table_evals[*index])
+                        .product::<F>()
+            })
+            .sum()
+    }
+
+    // fixes variable 0 of every table
+    pub fn partial_evaluate(&self, value: &F) -> Self {
+        Self {
+            tables: self
+                .tables
+                .iter()
+                .map(|table| table.partial_evaluate(0, value))
+                .collect(),
+            terms: self.terms.clone(),
+            n_vars: self.n_vars - 1,
+        }
+    }
+
+    pub fn sum_over_hypercube(&self) -> F {
+        (0..1 << self.n_vars)
+            .map(|i| {
+                self.terms
+                    .iter()
+                    .map(|(coefficient, indices)| {
+                        *coefficient
+                            * indices
+                                .iter()
+                                .map(|index| self.tables[*index].evals[i])
+                                .product::<F>()
+                    })
+                    .sum::<F>()
+            })
+            .sum()
+    }
+
+    pub fn get_degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|(_, indices)| indices.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl<F: PrimeField> From<&SumPoly<F>> for VirtualPoly<F> {
+    fn from(sum_poly: &SumPoly<F>) -> Self {
+        let mut virtual_poly = VirtualPoly::new(sum_poly.polys[0].evaluation[0].n_vars);
+
+        for product_poly in sum_poly.polys.iter() {
+            let indices = product_poly
+                .evaluation
+                .iter()
+                .map(|poly| virtual_poly.add_table(poly.clone()))
+                .collect();
+
+            virtual_poly.add_term(F::one(), indices);
+        }
+
+        virtual_poly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VirtualPoly;
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::multilinear_poly::{MultilinearPoly, ProductPoly, SumPoly};
+    use crate::libs::sum_check_protocol::{SumcheckProver, SumcheckVerifier};
+    use ark_bn254::Fr;
+
+    // 3 * a * b * c - 2 * b + 5 * a * a, with a, b, c over 2 variables
+    fn mixed_degree_poly() -> VirtualPoly<Fr> {
+        let mut poly = VirtualPoly::new(2);
+
+        let a = poly.add_table(MultilinearPoly::new(to_field(vec![1, 2, 3, 4])));
+        let b = poly.add_table(MultilinearPoly::new(to_field(vec![0, 5, 1, 2])));
+        let c = poly.add_table(MultilinearPoly::new(to_field(vec![7, 1, 0, 3])));
+
+        poly.add_term(Fr::from(3), vec![a, b, c]);
+        poly.add_term(-Fr::from(2), vec![b]);
+        poly.add_term(Fr::from(5), vec![a, a]);
+
+        poly
+    }
+
+    #[test]
+    fn it_evaluates_mixed_degree_terms() {
+        let poly = mixed_degree_poly();
+        let values = to_field(vec![2, 3]);
+
+        let a = poly.tables[0].evaluate(values.clone());
+        let b = poly.tables[1].evaluate(values.clone());
+        let c = poly.tables[2].evaluate(values.clone());
+
+        let expected = Fr::from(3) * a * b * c - Fr::from(2) * b + Fr::from(5) * a * a;
+
+        assert_eq!(poly.evaluate(&values), expected);
+        assert_eq!(poly.get_degree(), 3);
+    }
+
+    #[test]
+    fn it_sums_over_the_hypercube() {
+        let poly = mixed_degree_poly();
+
+        // 3 * (0 + 10 + 0 + 24) - 2 * 8 + 5 * (1 + 4 + 9 + 16)
+        assert_eq!(poly.sum_over_hypercube(), Fr::from(236));
+    }
+
+    #[test]
+    fn it_partially_evaluates_every_table() {
+        let poly = mixed_degree_poly();
+        let partial = poly.partial_evaluate(&Fr::from(2));
+
+        assert_eq!(partial.n_vars, 1);
+        assert_eq!(
+            partial.evaluate(&to_field(vec![3])),
+            poly.evaluate(&to_field(vec![2, 3]))
+        );
+    }
+
+    #[test]
+    fn it_converts_a_sum_poly() {
+        let sum_poly = SumPoly::new(vec![
+            ProductPoly::new(vec![to_field(vec![0, 0, 0, 3]), to_field(vec![0, 0, 0, 2])]),
+            ProductPoly::new(vec![to_field(vec![0, 0, 0, 4]), to_field(vec![0, 0, 0, 5])]),
+        ]);
+
+        let virtual_poly = VirtualPoly::from(&sum_poly);
+        let values = to_field(vec![2, 3]);
+
+        assert_eq!(virtual_poly.evaluate(&values), sum_poly.evaluate(values));
+    }
+
+    #[test]
+    fn it_runs_sumcheck_on_mixed_degree_terms() {
+        let poly = mixed_degree_poly();
+        let claimed_sum = poly.sum_over_hypercube();
+
+        let (proof, prover_claim) =
+            SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+
+        assert!(proof
+            .round_polys
+            .iter()
+            .all(|round_poly| round_poly.len() == poly.get_degree() + 1));
+
+        let claim = SumcheckVerifier::verify(&proof, 2, 3, &mut Transcript::new()).unwrap();
+
+        assert_eq!(claim, prover_claim);
+        assert_eq!(claim.value, poly.evaluate(&claim.point));
+    }
+}
@@ -0,0 +1,577 @@
+use super::fiat_shamir::Transcript;
+use super::pedersen::Opening;
+use super::polynomial::{barycentric_evaluate, UnivariatePolynomial};
+use super::sum_check_protocol::{SumcheckError, SumcheckPoly, SumcheckProver, SumcheckVerifier};
+use ark_ff::PrimeField;
+use ark_std::rand::Rng;
+use std::fmt::Debug;
+use std::iter;
+use std::ops::{Add, Mul};
+
+// g(x) = g_0(x_0) + g_1(x_1) + ... with every g_i of the sumcheck degree. the rounds of
+// f + rho * g are then random looking. g is only ever committed: its sum and its value
+// at the final point go into claims the verifier checks against the commitment, so the
+// claimed sum and f at the final point stay committed as well
+#[derive(Clone, Debug)]
+pub struct MaskingPoly<F: PrimeField> {
+    pub(crate) univariates: Vec<UnivariatePolynomial<F>>,
+}
+
+impl<F: PrimeField> MaskingPoly<F> {
+    pub fn random<R: Rng>(n_vars: usize, degree: usize, rng: &mut R) -> Self {
+        Self {
+            univariates: (0..n_vars)
+                .map(|_| UnivariatePolynomial::new((0..=degree).map(|_| F::rand(rng)).collect()))
+                .collect(),
+        }
+    }
+
+    pub fn n_vars(&self) -> usize {
+        self.univariates.len()
+    }
+
+    pub fn evaluate(&self, point: &[F]) -> F {
+        self.univariates
+            .iter()
+            .zip(point.iter())
+            .map(|(g_i, x)| g_i.evaluate(*x))
+            .sum()
+    }
+
+    // every g_i is summed over the other n - 1 variables
+    pub fn sum_over_hypercube(&self) -> F {
+        if self.univariates.is_empty() {
+            return F::zero();
+        }
+
+        let copies = F::from(2u64).pow([self.n_vars() as u64 - 1]);
+
+        self.univariates
+            .iter()
+            .map(|g_i| copies * (g_i.evaluate(F::zero()) + g_i.evaluate(F::one())))
+            .sum()
+    }
+
+    // g_0's coefficients, then g_1's, ..., lowest degree first
+    pub fn coefficients(&self) -> Vec<F> {
+        self.univariates
+            .iter()
+            .flat_map(|g_i| g_i.coefficients.iter().copied())
+            .collect()
+    }
+}
+
+// a vector commitment that adds up: commit(a, r) + commit(b, s) * x = commit(a + x * b, r + x * s).
+// that lets the verifier put rho * g next to a committed claim without opening either, and
+// all it then needs is a proof that a committed vector has some inner product with a public
+// one. PedersenParams is one such commitment
+pub trait HomomorphicCommitment<F: PrimeField> {
+    type Commitment: Clone
+        + Debug
+        + PartialEq
+        + Add<Output = Self::Commitment>
+        + Mul<F, Output = Self::Commitment>;
+    type DotProductProof;
+
+    // values go on the first values.len() generators
+    fn commit(&self, values: &[F], blind: F) -> Self::Commitment;
+    fn commitment_to_bytes(commitments: &[Self::Commitment]) -> Vec<u8>;
+
+    // <values, a> = y for the vector committed to with blind and a committed y
+    fn prove_dot_product<R: Rng>(
+        &self,
+        values: &[F],
+        blind: F,
+        a: &[F],
+        y: &Opening<F>,
+        transcript: &mut Transcript<F>,
+        rng: &mut R,
+    ) -> Self::DotProductProof;
+
+    fn verify_dot_product(
+        &self,
+        proof: &Self::DotProductProof,
+        commitment: &Self::Commitment,
+        y: &Self::Commitment,
+        a: &[F],
+        transcript: &mut Transcript<F>,
+    ) -> bool;
+}
+
+// g(point) = <coefficients, evaluation_form(point)>
+fn evaluation_form<F: PrimeField>(point: &[F], degree: usize) -> Vec<F> {
+    point
+        .iter()
+        .flat_map(|x| {
+            iter::successors(Some(F::one()), move |power| Some(*power * x)).take(degree + 1)
+        })
+        .collect()
+}
+
+// g's sum over the hypercube = <coefficients, sum_form(n_vars, degree)>. g_i(0) + g_i(1)
+// counts the constant twice and every other coefficient once
+fn sum_form<F: PrimeField>(n_vars: usize, degree: usize) -> Vec<F> {
+    if n_vars == 0 {
+        return vec![];
+    }
+
+    let copies = F::from(2u64).pow([n_vars as u64 - 1]);
+    let g_i = iter::once(copies.double()).chain(iter::repeat_n(copies, degree));
+
+    g_i.collect::<Vec<F>>().repeat(n_vars)
+}
+
+// the claim rides on the first generator and the mask on the ones after it, so
+// claim_commitment + rho * mask_commitment commits to (claim, rho * g's coefficients)
+fn masked_values<F: PrimeField>(claim: F, rho: F, coefficients: &[F]) -> Vec<F> {
+    iter::once(claim)
+        .chain(coefficients.iter().map(|c| rho * c))
+        .collect()
+}
+
+fn masked_form<F: PrimeField>(form: Vec<F>) -> Vec<F> {
+    iter::once(F::one()).chain(form).collect()
+}
+
+// how many generators prove_zk and verify_zk need
+pub fn commitment_len(n_vars: usize, degree: usize) -> usize {
+    1 + n_vars * (degree + 1)
+}
+
+#[derive(Clone, Debug)]
+pub struct ZkSumcheckProof<F: PrimeField, C: HomomorphicCommitment<F>> {
+    pub mask_commitment: C::Commitment,
+    // H + rho * (sum of g). the sum of g stays committed, so this says nothing about H
+    pub masked_sum: F,
+    pub masked_sum_proof: C::DotProductProof,
+    pub round_polys: Vec<Vec<F>>,
+    // f(r), which the verifier hands on as a commitment
+    pub evaluation_commitment: C::Commitment,
+    pub evaluation_proof: C::DotProductProof,
+}
+
+// the final claim on f with its value committed. whoever knows f opens it, e.g. GKR
+// ties it to the next layer
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommittedClaim<F: PrimeField, C: HomomorphicCommitment<F>> {
+    pub point: Vec<F>,
+    pub commitment: C::Commitment,
+}
+
+// f + rho * g, with the challenges fixed so far kept around to evaluate g's fixed part
+#[derive(Clone)]
+struct MaskedPoly<F: PrimeField, P: SumcheckPoly<F>> {
+    poly: P,
+    mask: MaskingPoly<F>,
+    rho: F,
+    challenges: Vec<F>,
+}
+
+impl<F: PrimeField, P: SumcheckPoly<F>> SumcheckPoly<F> for MaskedPoly<F, P> {
+    fn n_vars(&self) -> usize {
+        self.poly.n_vars()
+    }
+
+    fn get_degree(&self) -> usize {
+        self.poly.get_degree()
+    }
+
+    fn partial_evaluate(&self, value: &F) -> Self {
+        let mut result = self.clone();
+        result.fold(value);
+        result
+    }
+
+    fn sum_over_hypercube(&self) -> F {
+        self.poly.sum_over_hypercube() + self.rho * self.mask_round_sum(None)
+    }
+
+    fn round_polynomial(&self) -> Vec<F> {
+        let degree = SumcheckPoly::get_degree(self);
+        let evals = self.poly.round_polynomial();
+
+        (0..=degree as u64)
+            .map(|x| {
+                let x = F::from(x);
+                barycentric_evaluate(&evals, x) + self.rho * self.mask_round_sum(Some(x))
+            })
+            .collect()
+    }
+
+    fn fold(&mut self, challenge: &F) {
+        self.poly.fold(challenge);
+        self.challenges.push(*challenge);
+    }
+}
+
+impl<F: PrimeField, P: SumcheckPoly<F>> MaskedPoly<F, P> {
+    // g summed over the free variables, with the current one set to x when given
+    fn mask_round_sum(&self, x: Option<F>) -> F {
+        let round = self.challenges.len();
+        let free = self.mask.n_vars() - round - x.is_some() as usize;
+        let copies = F::from(2u64).pow([free as u64]);
+
+        let fixed: F = self.mask.univariates[..round]
+            .iter()
+            .zip(self.challenges.iter())
+            .map(|(g_i, r)| g_i.evaluate(*r))
+            .sum::<F>()
+            + x.map_or(F::zero(), |x| self.mask.univariates[round].evaluate(x));
+
+        // a free g_i takes 0 and 1 in equally many of the other free points
+        let rest = if free == 0 {
+            F::zero()
+        } else {
+            F::from(2u64).pow([free as u64 - 1])
+                * self.mask.univariates[self.mask.n_vars() - free..]
+                    .iter()
+                    .map(|g_i| g_i.evaluate(F::zero()) + g_i.evaluate(F::one()))
+                    .sum::<F>()
+        };
+
+        copies * fixed + rest
+    }
+}
+
+fn absorb_mask<F: PrimeField, C: HomomorphicCommitment<F>>(
+    claim_commitment: &C::Commitment,
+    mask_commitment: &C::Commitment,
+    transcript: &mut Transcript<F>,
+) -> F {
+    transcript.append(&C::commitment_to_bytes(&[
+        claim_commitment.clone(),
+        mask_commitment.clone(),
+    ]));
+
+    transcript.get_random_challenge()
+}
+
+// proves that the claim committed to with claim's blind is the sum of poly. a public sum
+// is a claim with a zero blind. hands back the final point with the opening of f there
+pub fn prove_zk<F, C, P, R>(
+    scheme: &C,
+    poly: &P,
+    claim: &Opening<F>,
+    transcript: &mut Transcript<F>,
+    rng: &mut R,
+) -> (ZkSumcheckProof<F, C>, Vec<F>, Opening<F>)
+where
+    F: PrimeField,
+    C: HomomorphicCommitment<F>,
+    P: SumcheckPoly<F>,
+    R: Rng,
+{
+    let (n_vars, degree) = (poly.n_vars(), poly.get_degree());
+
+    let mask = MaskingPoly::random(n_vars, degree, rng);
+    let coefficients = mask.coefficients();
+    let mask_blind = F::rand(rng);
+    let mask_commitment = scheme.commit(
+        &masked_values(F::zero(), F::one(), &coefficients),
+        mask_blind,
+    );
+
+    let rho = absorb_mask::<F, C>(
+        &scheme.commit(&[claim.value], claim.blind),
+        &mask_commitment,
+        transcript,
+    );
+
+    let masked_sum = claim.value + rho * mask.sum_over_hypercube();
+    let masked_sum_proof = scheme.prove_dot_product(
+        &masked_values(claim.value, rho, &coefficients),
+        claim.blind + rho * mask_blind,
+        &masked_form(sum_form(n_vars, degree)),
+        &Opening {
+            value: masked_sum,
+            blind: F::zero(),
+        },
+        transcript,
+        rng,
+    );
+
+    let masked_poly = MaskedPoly {
+        poly: poly.clone(),
+        mask,
+        rho,
+        challenges: vec![],
+    };
+
+    let (proof, challenges, folded) =
+        SumcheckProver::prove_rounds(&masked_poly, masked_sum, transcript);
+
+    let evaluation = Opening::random(folded.poly.sum_over_hypercube(), rng);
+    let final_value = evaluation.value + rho * folded.mask.evaluate(&challenges);
+    let evaluation_proof = scheme.prove_dot_product(
+        &masked_values(evaluation.value, rho, &coefficients),
+        evaluation.blind + rho * mask_blind,
+        &masked_form(evaluation_form(&challenges, degree)),
+        &Opening {
+            value: final_value,
+            blind: F::zero(),
+        },
+        transcript,
+        rng,
+    );
+
+    let zk_proof = ZkSumcheckProof {
+        mask_commitment,
+        masked_sum,
+        masked_sum_proof,
+        round_polys: proof.round_polys,
+        evaluation_commitment: scheme.commit(&[evaluation.value], evaluation.blind),
+        evaluation_proof,
+    };
+
+    (zk_proof, challenges, evaluation)
+}
+
+pub fn verify_zk<F: PrimeField, C: HomomorphicCommitment<F>>(
+    scheme: &C,
+    proof: &ZkSumcheckProof<F, C>,
+    claim_commitment: &C::Commitment,
+    n_vars: usize,
+    degree: usize,
+    transcript: &mut Transcript<F>,
+) -> Result<CommittedClaim<F, C>, SumcheckError> {
+    let rho = absorb_mask::<F, C>(claim_commitment, &proof.mask_commitment, transcript);
+
+    if !scheme.verify_dot_product(
+        &proof.masked_sum_proof,
+        &(claim_commitment.clone() + proof.mask_commitment.clone() * rho),
+        &scheme.commit(&[proof.masked_sum], F::zero()),
+        &masked_form(sum_form(n_vars, degree)),
+        transcript,
+    ) {
+        return Err(SumcheckError::InvalidMaskOpening);
+    }
+
+    let claim = SumcheckVerifier::verify_rounds(
+        proof.masked_sum,
+        &proof.round_polys,
+        n_vars,
+        degree,
+        false,
+        transcript,
+    )?;
+
+    if !scheme.verify_dot_product(
+        &proof.evaluation_proof,
+        &(proof.evaluation_commitment.clone() + proof.mask_commitment.clone() * rho),
+        &scheme.commit(&[claim.value], F::zero()),
+        &masked_form(evaluation_form(&claim.point, degree)),
+        transcript,
+    ) {
+        return Err(SumcheckError::InvalidMaskOpening);
+    }
+
+    Ok(CommittedClaim {
+        point: claim.point,
+        commitment: proof.evaluation_commitment.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{commitment_len, prove_zk, verify_zk, MaskingPoly};
+    use crate::libs::fiat_shamir::Transcript;
+    use crate::libs::multilinear_poly::tests::to_field;
+    use crate::libs::multilinear_poly::{MultilinearPoly, ProductPoly, SumPoly};
+    use crate::libs::pedersen::{public_commitment, Opening, PedersenParams};
+    use crate::libs::sum_check_protocol::{SumcheckError, SumcheckPoly, SumcheckProver};
+    use ark_bn254::{g1::Config, Fr};
+    use ark_std::test_rng;
+
+    fn poly() -> SumPoly<Fr> {
+        SumPoly::new(vec![
+            ProductPoly::new(vec![
+                to_field(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+                to_field(vec![0, 5, 1, 2, 9, 9, 4, 1]),
+            ]),
+            ProductPoly::new(vec![
+                to_field(vec![3, 0, 0, 1, 2, 0, 0, 7]),
+                to_field(vec![1, 1, 2, 2, 3, 3, 4, 4]),
+            ]),
+        ])
+    }
+
+    fn params() -> PedersenParams<Config> {
+        PedersenParams::new(commitment_len(3, 2), b"zk sumcheck tests")
+    }
+
+    #[test]
+    fn masking_poly_sums_like_its_table() {
+        let mask = MaskingPoly::<Fr>::random(3, 2, &mut test_rng());
+
+        let table: Vec<Fr> = (0..8)
+            .map(|i| {
+                let point: Vec<Fr> = (0..3).map(|bit| Fr::from((i >> (2 - bit)) & 1)).collect();
+                mask.evaluate(&point)
+            })
+            .collect();
+
+        assert_eq!(mask.sum_over_hypercube(), table.iter().sum::<Fr>());
+    }
+
+    #[test]
+    fn it_proves_a_masked_sum() {
+        let params = params();
+        let poly = poly();
+        let claimed_sum = poly.sum_over_hypercube();
+        let claim = Opening {
+            value: claimed_sum,
+            blind: Fr::from(0),
+        };
+
+        let (proof, prover_point, evaluation) = prove_zk(
+            &params,
+            &poly,
+            &claim,
+            &mut Transcript::new(),
+            &mut test_rng(),
+        );
+
+        let claim = verify_zk(
+            &params,
+            &proof,
+            &public_commitment(&params, claimed_sum),
+            3,
+            2,
+            &mut Transcript::new(),
+        )
+        .unwrap();
+
+        assert_eq!(claim.point, prover_point);
+        assert!(params.check_opening(&claim.commitment, &evaluation));
+        assert_eq!(evaluation.value, poly.evaluate(claim.point.clone()));
+
+        // the rounds are not the rounds of f
+        let (plain, _) = SumcheckProver::prove(&poly, claimed_sum, &mut Transcript::new());
+        assert_ne!(proof.round_polys[0], plain.round_polys[0]);
+    }
+
+    #[test]
+    fn it_proves_a_committed_sum() {
+        let params = params();
+        let poly = poly();
+        let mut rng = test_rng();
+        let claim = Opening::random(poly.sum_over_hypercube(), &mut rng);
+
+        let (proof, _, _) = prove_zk(&params, &poly, &claim, &mut Transcript::new(), &mut rng);
+
+        // nothing in the proof is the sum itself
+        assert_ne!(proof.masked_sum, claim.value);
+        assert!(verify_zk(
+            &params,
+            &proof,
+            &params.commit_opening(&claim),
+            3,
+            2,
+            &mut Transcript::new()
+        )
+        .is_ok());
+
+        let other_claim = Opening {
+            value: claim.value + Fr::from(1),
+            blind: claim.blind,
+        };
+        assert_eq!(
+            verify_zk(
+                &params,
+                &proof,
+                &params.commit_opening(&other_claim),
+                3,
+                2,
+                &mut Transcript::new()
+            )
+            .err(),
+            Some(SumcheckError::InvalidMaskOpening)
+        );
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_sum_or_mask() {
+        let params = params();
+        let poly = poly();
+        let claimed_sum = poly.sum_over_hypercube();
+        let public = |value: Fr| Opening {
+            value,
+            blind: Fr::from(0),
+        };
+        let verify = |proof, sum| {
+            verify_zk(
+                &params,
+                proof,
+                &public_commitment(&params, sum),
+                3,
+                2,
+                &mut Transcript::new(),
+            )
+            .err()
+        };
+
+        let wrong_sum = claimed_sum + Fr::from(1);
+        let (proof, _, _) = prove_zk(
+            &params,
+            &poly,
+            &public(wrong_sum),
+            &mut Transcript::new(),
+            &mut test_rng(),
+        );
+        assert_eq!(
+            verify(&proof, wrong_sum),
+            Some(SumcheckError::RoundSumMismatch { round: 0 })
+        );
+
+        let prove = || {
+            prove_zk(
+                &params,
+                &poly,
+                &public(claimed_sum),
+                &mut Transcript::new(),
+                &mut test_rng(),
+            )
+            .0
+        };
+
+        let mut proof = prove();
+        proof.masked_sum += Fr::from(1);
+        assert_eq!(
+            verify(&proof, claimed_sum),
+            Some(SumcheckError::InvalidMaskOpening)
+        );
+
+        let mut proof = prove();
+        proof.evaluation_commitment += params.generators[0];
+        assert_eq!(
+            verify(&proof, claimed_sum),
+            Some(SumcheckError::InvalidMaskOpening)
+        );
+
+        let mut proof = prove();
+        proof.mask_commitment += params.generators[1];
+        assert!(verify(&proof, claimed_sum).is_some());
+
+        // a multilinear f still works, with a degree 1 mask
+        let f = MultilinearPoly::new(to_field(vec![0, 0, 0, 3, 0, 0, 2, 5]));
+        let params = PedersenParams::<Config>::new(commitment_len(3, 1), b"zk sumcheck tests");
+        let (proof, _, evaluation) = prove_zk(
+            &params,
+            &f,
+            &public(Fr::from(10)),
+            &mut Transcript::new(),
+            &mut test_rng(),
+        );
+        let claim = verify_zk(
+            &params,
+            &proof,
+            &public_commitment(&params, Fr::from(10)),
+            3,
+            1,
+            &mut Transcript::new(),
+        )
+        .unwrap();
+        assert!(params.check_opening(&claim.commitment, &evaluation));
+        assert_eq!(evaluation.value, f.evaluate(claim.point));
+    }
+}
@@ -1,8 +1,3 @@
-use ark_bn254::Fq;
-use libs::polynomial::UnivariatePolynomial;
-
-mod libs;
-
 fn main() {
     // println!(
     //     "{:?}",